@@ -0,0 +1,154 @@
+/// Types related to virtual text that is displayed next to the lines of a `Pager` without being
+/// part of the content.
+///
+/// `Annotations` of a `PagerContent` can be accessed using `PagerContent::annotations_mut`.
+use unsegen::base::{LineIndex, StyleModifier};
+
+use super::text;
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Specifies where an `Annotation` is displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotationPosition {
+    /// Directly after the end of the line (separated by a space).
+    EndOfLine,
+    /// At the right border of the window.
+    RightAligned,
+}
+
+/// Virtual text (e.g., an inline hint or a diagnostic) that is displayed next to a line.
+///
+/// Annotations are not part of the content, so they are not searched and are not considered when
+/// wrapping lines. Instead, they are cut off if they do not fit into the last row of the line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    /// The text that is displayed.
+    pub text: String,
+    /// The style that is applied to the text (on top of the style of the line).
+    pub style: StyleModifier,
+    /// Where the text is displayed.
+    pub position: AnnotationPosition,
+}
+
+impl Annotation {
+    /// Create an annotation that is displayed after the end of the line.
+    pub fn end_of_line<S: Into<String>>(text: S, style: StyleModifier) -> Self {
+        Annotation {
+            text: text.into(),
+            style,
+            position: AnnotationPosition::EndOfLine,
+        }
+    }
+
+    /// Create an annotation that is displayed at the right border of the window.
+    pub fn right_aligned<S: Into<String>>(text: S, style: StyleModifier) -> Self {
+        Annotation {
+            text: text.into(),
+            style,
+            position: AnnotationPosition::RightAligned,
+        }
+    }
+}
+
+/// The annotations of all lines of a `PagerContent`.
+///
+/// Annotations are moved along with their lines if lines are inserted or removed using
+/// `PagerContent::replace_range` (or `Pager::replace_range`). Annotations of replaced lines are
+/// removed.
+#[derive(Clone, Debug, Default)]
+pub struct Annotations {
+    lines: BTreeMap<usize, Vec<Annotation>>,
+}
+
+impl Annotations {
+    /// Add an annotation to a line. Multiple annotations of the same line are displayed in the
+    /// order they were added.
+    pub fn add<I: Into<LineIndex>>(&mut self, line: I, annotation: Annotation) {
+        self.lines
+            .entry(line.into().raw_value())
+            .or_default()
+            .push(annotation);
+    }
+
+    /// Remove all annotations of a line.
+    pub fn clear_line<I: Into<LineIndex>>(&mut self, line: I) {
+        self.lines.remove(&line.into().raw_value());
+    }
+
+    /// Remove all annotations of all lines.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Get the annotations of a line.
+    pub fn for_line<I: Into<LineIndex>>(&self, line: I) -> &[Annotation] {
+        self.lines
+            .get(&line.into().raw_value())
+            .map(|annotations| &annotations[..])
+            .unwrap_or(&[])
+    }
+
+    /// Update the line indices of all annotations after the lines in `range` have been replaced
+    /// by `num_new_lines` lines.
+    pub(crate) fn lines_replaced(&mut self, range: Range<usize>, num_new_lines: usize) {
+        let moved = self.lines.split_off(&range.start).split_off(&range.end);
+        self.lines.extend(
+            moved
+                .into_iter()
+                .map(|(line, annotations)| (line - range.len() + num_new_lines, annotations)),
+        );
+    }
+
+    /// Determine the columns at which the annotations of a line are drawn, if the line ends at
+    /// column `line_end` of a window with `width` columns.
+    ///
+    /// Annotations that do not fit are cut off (at the end of the line) or omitted (at the right
+    /// border).
+    pub(crate) fn layout(
+        &self,
+        line: LineIndex,
+        line_end: usize,
+        width: usize,
+    ) -> Vec<(usize, &str, StyleModifier)> {
+        let annotations = self.for_line(line);
+        let mut result = Vec::new();
+
+        let mut column = line_end;
+        for annotation in annotations
+            .iter()
+            .filter(|a| a.position == AnnotationPosition::EndOfLine)
+        {
+            column += 1;
+            if column >= width {
+                break;
+            }
+            let text = text::truncate_to_width(&annotation.text, width - column);
+            result.push((column, text, annotation.style));
+            column += text::width(text);
+        }
+
+        let right_aligned = annotations
+            .iter()
+            .filter(|a| a.position == AnnotationPosition::RightAligned)
+            .collect::<Vec<_>>();
+        if !right_aligned.is_empty() {
+            let total_width = right_aligned
+                .iter()
+                .map(|a| text::width(&a.text))
+                .sum::<usize>()
+                + right_aligned.len()
+                - 1;
+            // Keep a space between the line (or end-of-line annotations) and the annotations.
+            if column + 1 + total_width <= width {
+                let mut column = width - total_width;
+                for annotation in right_aligned {
+                    result.push((column, &annotation.text[..], annotation.style));
+                    column += text::width(&annotation.text) + 1;
+                }
+            }
+        }
+        result
+    }
+}
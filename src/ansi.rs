@@ -0,0 +1,409 @@
+/// Types related to content that is colored using ANSI escape sequences, like the output of
+/// compilers, test runners or `git`.
+///
+/// `AnsiParser` removes escape sequences from lines and converts SGR ("Select Graphic Rendition")
+/// sequences to style changes. Use `PagerContent::from_ansi_lines` or
+/// `PagerContent::from_ansi_file` to build content from such text directly and
+/// `PagerContent::append_ansi_lines` to extend it.
+use unsegen::base::{Color, StyleModifier};
+
+use super::HighlightInfo;
+
+/// A parameter of an SGR sequence followed by its sub-parameters (separated by ':'), if any, so
+/// there is always at least one value.
+///
+/// Empty values are `None`, values that are too large to be represented are `u32::MAX`.
+type SgrParameter = Vec<Option<u32>>;
+
+fn parse_sgr_parameter(parameter: &str) -> SgrParameter {
+    parameter
+        .split(':')
+        .map(|value| {
+            if value.is_empty() {
+                None
+            } else {
+                Some(value.parse::<u32>().unwrap_or(u32::MAX))
+            }
+        })
+        .collect()
+}
+
+/// Text attributes as set by SGR escape sequences.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct AnsiStyle {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    invert: bool,
+}
+
+impl AnsiStyle {
+    fn to_style_modifier(self) -> StyleModifier {
+        let mut style = StyleModifier::new();
+        if let Some(fg) = self.fg {
+            style = style.fg_color(fg);
+        }
+        if let Some(bg) = self.bg {
+            style = style.bg_color(bg);
+        }
+        if self.bold {
+            style = style.bold(true);
+        }
+        if self.italic {
+            style = style.italic(true);
+        }
+        if self.underline {
+            style = style.underline(true);
+        }
+        if self.invert {
+            style = style.invert(true);
+        }
+        style
+    }
+
+    /// Update the style according to the parameters of an SGR sequence.
+    ///
+    /// Colors are specified either using separate parameters (`38;2;r;g;b`) or using
+    /// sub-parameters (`38:2:r:g:b` or, with a color space, `38:2::r:g:b`). Sub-parameters of
+    /// other attributes are ignored, except for the underline style (`4:0` disables underlining).
+    fn apply_sgr(&mut self, params: &[SgrParameter]) {
+        if params.is_empty() {
+            *self = AnsiStyle::default();
+            return;
+        }
+        let mut params = params.iter();
+        while let Some(param) = params.next() {
+            let code = param[0].unwrap_or(0);
+            let sub_params = &param[1..];
+            let mut color = || {
+                if sub_params.is_empty() {
+                    extended_color(&mut params.by_ref().map(|p| p[0]))
+                } else {
+                    extended_color_from_sub_params(sub_params)
+                }
+            };
+            match code {
+                0 => *self = AnsiStyle::default(),
+                1 => self.bold = true,
+                3 => self.italic = true,
+                4 => self.underline = sub_params.first() != Some(&Some(0)),
+                7 => self.invert = true,
+                22 => self.bold = false,
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.invert = false,
+                30..=37 => self.fg = Some(basic_color(code - 30)),
+                38 => self.fg = color().or(self.fg),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(basic_color(code - 40)),
+                48 => self.bg = color().or(self.bg),
+                49 => self.bg = None,
+                90..=97 => self.fg = Some(bright_color(code - 90)),
+                100..=107 => self.bg = Some(bright_color(code - 100)),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn basic_color(index: u32) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::White,
+    }
+}
+
+fn bright_color(index: u32) -> Color {
+    match index {
+        0 => Color::LightBlack,
+        1 => Color::LightRed,
+        2 => Color::LightGreen,
+        3 => Color::LightYellow,
+        4 => Color::LightBlue,
+        5 => Color::LightMagenta,
+        6 => Color::LightCyan,
+        _ => Color::LightWhite,
+    }
+}
+
+/// Parse the remainder of a 256 color (`5;n`) or truecolor (`2;r;g;b`) specification.
+///
+/// Only the values that belong to the specification are consumed. Specifications with values
+/// above 255 are invalid and result in `None`. Empty values are interpreted as 0.
+fn extended_color<I: Iterator<Item = Option<u32>>>(values: &mut I) -> Option<Color> {
+    match values.next()? {
+        Some(5) => color_component(values.next()?).map(Color::Ansi),
+        Some(2) => {
+            let r = values.next()?;
+            let g = values.next()?;
+            let b = values.next()?;
+            Some(Color::Rgb {
+                r: color_component(r)?,
+                g: color_component(g)?,
+                b: color_component(b)?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Parse the sub-parameters of a color specification (`5:n`, `2:r:g:b` or `2:cs:r:g:b`, where
+/// `cs` is an ignored color space identifier).
+fn extended_color_from_sub_params(sub_params: &[Option<u32>]) -> Option<Color> {
+    let (kind, values) = sub_params.split_first()?;
+    let values = if *kind == Some(2) && values.len() >= 4 {
+        &values[1..]
+    } else {
+        values
+    };
+    extended_color(&mut ::std::iter::once(kind).chain(values).cloned())
+}
+
+fn color_component(value: Option<u32>) -> Option<u8> {
+    let value = value.unwrap_or(0);
+    if value <= 255 {
+        Some(value as u8)
+    } else {
+        None
+    }
+}
+
+/// Removes ANSI escape sequences from lines and converts SGR sequences to style changes.
+///
+/// The style is carried over from one line to the next, so all lines of a text should be parsed
+/// in order using the same parser.
+#[derive(Clone, Debug, Default)]
+pub struct AnsiParser {
+    style: AnsiStyle,
+}
+
+impl AnsiParser {
+    /// Create a parser for a new text, i.e., with no style set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a single line of text.
+    ///
+    /// Returns the line without escape sequences and the style changes (as in
+    /// `HighlightInfo::style_changes`) of the line.
+    pub fn parse_line(&mut self, line: &str) -> (String, Vec<(usize, StyleModifier)>) {
+        let mut content = String::with_capacity(line.len());
+        let mut changes = Vec::new();
+        if self.style != AnsiStyle::default() {
+            changes.push((0, self.style.to_style_modifier()));
+        }
+
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                content.push(c);
+                continue;
+            }
+            match chars.next() {
+                // Control Sequence Introducer: Parameters and intermediate bytes are followed by
+                // a final byte in the range '@' to '~'.
+                Some('[') => {
+                    let mut sequence = String::new();
+                    let mut final_byte = None;
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            final_byte = Some(c);
+                            break;
+                        }
+                        sequence.push(c);
+                    }
+                    if final_byte == Some('m') {
+                        let params = sequence
+                            .split(';')
+                            .map(parse_sgr_parameter)
+                            .collect::<Vec<_>>();
+                        let previous_style = self.style;
+                        self.style.apply_sgr(&params);
+                        if self.style != previous_style {
+                            let style = self.style.to_style_modifier();
+                            match changes.last_mut() {
+                                Some(&mut (pos, ref mut last_style)) if pos == content.len() => {
+                                    *last_style = style;
+                                }
+                                _ => changes.push((content.len(), style)),
+                            }
+                        }
+                    }
+                }
+                // Operating System Command: Terminated by BEL or ST (ESC \).
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Any other escape sequence consists of a single additional character.
+                _ => {}
+            }
+        }
+        (content, changes)
+    }
+}
+
+/// Highlighting of content that is built from lines with ANSI escape sequences.
+///
+/// The state of the parser after each line is kept, so that lines can be appended to the content
+/// (and removed from its end) later on.
+pub(crate) struct AnsiHighlighting {
+    info: HighlightInfo,
+    states: Vec<AnsiParser>,
+}
+
+impl AnsiHighlighting {
+    /// Create the highlighting of an empty content.
+    pub fn new() -> Self {
+        AnsiHighlighting {
+            info: HighlightInfo::none(),
+            states: Vec::new(),
+        }
+    }
+
+    /// Parse lines that are appended to the content, continuing with the style at the end of the
+    /// last line, and return them without escape sequences.
+    pub fn append_lines<S: AsRef<str>, I: IntoIterator<Item = S>>(
+        &mut self,
+        lines: I,
+    ) -> Vec<String> {
+        let mut parser = self.states.last().cloned().unwrap_or_default();
+        let mut contents = Vec::new();
+        for line in lines {
+            let (content, style_changes) = parser.parse_line(line.as_ref());
+            contents.push(content);
+            self.info.style_changes.push(style_changes);
+            self.states.push(parser.clone());
+        }
+        contents
+    }
+
+    /// Remove the highlighting of all lines after the first `num_lines` lines.
+    pub fn truncate(&mut self, num_lines: usize) {
+        self.info.style_changes.truncate(num_lines);
+        self.states.truncate(num_lines);
+    }
+
+    /// Get the highlighting information of all lines.
+    pub fn info(&self) -> &HighlightInfo {
+        &self.info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> (String, Vec<(usize, StyleModifier)>) {
+        AnsiParser::new().parse_line(line)
+    }
+
+    #[test]
+    fn removes_escape_sequences() {
+        let (content, changes) = parse("a\x1b[1mb\x1b[0mc\x1b]0;title\x07d\x1b[2Ke");
+        assert_eq!(content, "abcde");
+        assert_eq!(
+            changes,
+            vec![
+                (1, StyleModifier::new().bold(true)),
+                (2, StyleModifier::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn basic_colors() {
+        let (content, changes) = parse("\x1b[31;42mx\x1b[39;49my");
+        assert_eq!(content, "xy");
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    0,
+                    StyleModifier::new()
+                        .fg_color(Color::Red)
+                        .bg_color(Color::Green)
+                ),
+                (1, StyleModifier::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn extended_colors() {
+        let rgb = StyleModifier::new().fg_color(Color::Rgb { r: 1, g: 2, b: 3 });
+        for sequence in &["38;2;1;2;3", "38:2:1:2:3", "38:2::1:2:3", "38:2:0:1:2:3"] {
+            let (_, changes) = parse(&format!("\x1b[{}mx", sequence));
+            assert_eq!(changes, vec![(0, rgb)], "{}", sequence);
+        }
+        let (_, changes) = parse("\x1b[48;5;200;1mx");
+        assert_eq!(
+            changes,
+            vec![(
+                0,
+                StyleModifier::new().bg_color(Color::Ansi(200)).bold(true)
+            )]
+        );
+        let (_, changes) = parse("\x1b[48:5:200mx");
+        assert_eq!(
+            changes,
+            vec![(0, StyleModifier::new().bg_color(Color::Ansi(200)))]
+        );
+    }
+
+    #[test]
+    fn truecolor_out_of_range_is_ignored() {
+        let (_, changes) = parse("\x1b[38;2;256;0;0;1mx");
+        assert_eq!(changes, vec![(0, StyleModifier::new().bold(true))]);
+        let (_, changes) = parse("\x1b[38:2::0:0:99999999999mx");
+        assert_eq!(changes, vec![]);
+        let (_, changes) = parse("\x1b[32m\x1b[38;5;300mx");
+        assert_eq!(
+            changes,
+            vec![(0, StyleModifier::new().fg_color(Color::Green))]
+        );
+    }
+
+    #[test]
+    fn unknown_sub_parameters_are_skipped() {
+        let (_, changes) = parse("\x1b[4:3;1mx\x1b[4:0my");
+        assert_eq!(
+            changes,
+            vec![
+                (0, StyleModifier::new().bold(true).underline(true)),
+                (1, StyleModifier::new().bold(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn style_carries_over_to_next_line() {
+        let mut parser = AnsiParser::new();
+        parser.parse_line("\x1b[3mfoo");
+        let (content, changes) = parser.parse_line("bar\x1b[mbaz");
+        assert_eq!(content, "barbaz");
+        assert_eq!(
+            changes,
+            vec![
+                (0, StyleModifier::new().italic(true)),
+                (3, StyleModifier::new()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,713 @@
+/// Ready-made key bindings that operate a `Pager`, similar to `less` or `vim`.
+///
+/// `PagerKeyBindings` maps (sequences of) keys to `PagerAction`s. `PagerBehavior` executes them on
+/// a pager when chained to an `Input`. State that spans multiple keys (e.g., numeric prefixes or a
+/// search pattern that is being typed) is kept in a `PagerKeyState` owned by the caller.
+use unsegen::base::LineIndex;
+use unsegen::input::{Behavior, Event, Input, Key, OperationResult, Scrollable};
+
+use super::{LineDecorator, Pager, PagerLine};
+
+/// Operations on a `Pager` that can be bound to keys.
+///
+/// Many actions can be prefixed with a count (e.g., `42g` in `less`). Unless documented otherwise
+/// for an action, line- and cursor-based actions (and others that can be repeated) are repeated
+/// `count` times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PagerAction {
+    LineForwards,
+    LineBackwards,
+    /// Move forwards by the height of the window, or by `count` lines if a count is given.
+    PageForwards,
+    /// Move backwards by the height of the window, or by `count` lines if a count is given.
+    PageBackwards,
+    /// Move forwards by half the height of the window. A count sets the number of lines to move
+    /// by for this and all following half page actions.
+    HalfPageForwards,
+    /// Move backwards by half the height of the window. A count sets the number of lines to move
+    /// by for this and all following half page actions.
+    HalfPageBackwards,
+    /// Go to the first line, or to line `count` (starting at 1) if a count is given.
+    GoToBeginning,
+    /// Go to the last line, or to line `count` (starting at 1) if a count is given.
+    GoToEnd,
+    /// Move the visible part of truncated lines to the left by half the screen width.
+    ScrollLeft,
+    /// Move the visible part of truncated lines to the right by half the screen width.
+    ScrollRight,
+    CursorLeft,
+    CursorRight,
+    CursorWordForwards,
+    CursorWordBackwards,
+    CursorLineStart,
+    CursorLineEnd,
+    /// Start typing a search pattern, which is searched for forwards when `Key::Char('\n')` is
+    /// pressed.
+    SearchForwards,
+    /// Start typing a search pattern, which is searched for backwards when `Key::Char('\n')` is
+    /// pressed.
+    SearchBackwards,
+    /// Go to the next match in the direction of the last search.
+    NextMatch,
+    /// Go to the next match in the opposite direction of the last search.
+    PreviousMatch,
+    /// Enable or disable follow mode.
+    ToggleFollowMode,
+    /// Set the mark named by the next key (a character).
+    SetMark,
+    /// Go to the mark named by the next key (a character). `'` goes back to the line that was
+    /// active before the last jump.
+    GoToMark,
+    JumpBack,
+    JumpForward,
+    ToggleFold,
+    OpenFold,
+    CloseFold,
+    OpenAllFolds,
+    CloseAllFolds,
+    /// Start selecting lines, or clear the selection if there is one.
+    ToggleSelection,
+    /// Cancel anything that is pending (e.g., a count) and clear the selection.
+    Cancel,
+}
+
+/// A remappable mapping of key sequences to `PagerAction`s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PagerKeyBindings {
+    bindings: Vec<(Vec<Key>, PagerAction)>,
+}
+
+impl PagerKeyBindings {
+    /// Create bindings without any keys bound.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create bindings that resemble the ones of `less`.
+    pub fn less() -> Self {
+        use self::PagerAction::*;
+        PagerKeyBindings::new()
+            .bind_all(
+                &[
+                    Key::Down,
+                    Key::Char('j'),
+                    Key::Char('e'),
+                    Key::Char('\n'),
+                    Key::Ctrl('n'),
+                    Key::Ctrl('e'),
+                ],
+                LineForwards,
+            )
+            .bind_all(
+                &[
+                    Key::Up,
+                    Key::Char('k'),
+                    Key::Char('y'),
+                    Key::Ctrl('p'),
+                    Key::Ctrl('y'),
+                ],
+                LineBackwards,
+            )
+            .bind_all(
+                &[
+                    Key::Char(' '),
+                    Key::Char('f'),
+                    Key::PageDown,
+                    Key::Ctrl('f'),
+                    Key::Ctrl('v'),
+                ],
+                PageForwards,
+            )
+            .bind_all(
+                &[Key::Char('b'), Key::PageUp, Key::Ctrl('b'), Key::Alt('v')],
+                PageBackwards,
+            )
+            .bind_all(&[Key::Char('d'), Key::Ctrl('d')], HalfPageForwards)
+            .bind_all(&[Key::Char('u'), Key::Ctrl('u')], HalfPageBackwards)
+            .bind_all(&[Key::Char('g'), Key::Char('<'), Key::Home], GoToBeginning)
+            .bind_all(&[Key::Char('G'), Key::Char('>'), Key::End], GoToEnd)
+            .bind_all(&[Key::Left], ScrollLeft)
+            .bind_all(&[Key::Right], ScrollRight)
+            .bind_all(&[Key::Char('/')], SearchForwards)
+            .bind_all(&[Key::Char('?')], SearchBackwards)
+            .bind_all(&[Key::Char('n')], NextMatch)
+            .bind_all(&[Key::Char('N')], PreviousMatch)
+            .bind_all(&[Key::Char('F')], ToggleFollowMode)
+            .bind_all(&[Key::Char('m')], SetMark)
+            .bind_all(&[Key::Char('\'')], GoToMark)
+            .bind_all(&[Key::Esc], Cancel)
+    }
+
+    /// Create bindings that resemble the ones of `vim`.
+    ///
+    /// `h`, `l`, `w`, `b`, `0` and `$` move the column cursor (see `Pager::set_column_cursor`).
+    pub fn vim() -> Self {
+        use self::PagerAction::*;
+        PagerKeyBindings::new()
+            .bind_all(&[Key::Down, Key::Char('j'), Key::Ctrl('e')], LineForwards)
+            .bind_all(&[Key::Up, Key::Char('k'), Key::Ctrl('y')], LineBackwards)
+            .bind_all(&[Key::PageDown, Key::Ctrl('f')], PageForwards)
+            .bind_all(&[Key::PageUp, Key::Ctrl('b')], PageBackwards)
+            .bind_all(&[Key::Ctrl('d')], HalfPageForwards)
+            .bind_all(&[Key::Ctrl('u')], HalfPageBackwards)
+            .bind(&[Key::Char('g'), Key::Char('g')], GoToBeginning)
+            .bind_all(&[Key::Home], GoToBeginning)
+            .bind_all(&[Key::Char('G'), Key::End], GoToEnd)
+            .bind(&[Key::Char('z'), Key::Char('h')], ScrollLeft)
+            .bind(&[Key::Char('z'), Key::Char('l')], ScrollRight)
+            .bind_all(&[Key::Char('h'), Key::Left], CursorLeft)
+            .bind_all(&[Key::Char('l'), Key::Right], CursorRight)
+            .bind_all(&[Key::Char('w')], CursorWordForwards)
+            .bind_all(&[Key::Char('b')], CursorWordBackwards)
+            .bind_all(&[Key::Char('0')], CursorLineStart)
+            .bind_all(&[Key::Char('$')], CursorLineEnd)
+            .bind_all(&[Key::Char('/')], SearchForwards)
+            .bind_all(&[Key::Char('?')], SearchBackwards)
+            .bind_all(&[Key::Char('n')], NextMatch)
+            .bind_all(&[Key::Char('N')], PreviousMatch)
+            .bind_all(&[Key::Char('m')], SetMark)
+            .bind_all(&[Key::Char('\''), Key::Char('`')], GoToMark)
+            .bind_all(&[Key::Ctrl('o')], JumpBack)
+            .bind_all(&[Key::Char('\t')], JumpForward)
+            .bind(&[Key::Char('z'), Key::Char('a')], ToggleFold)
+            .bind(&[Key::Char('z'), Key::Char('o')], OpenFold)
+            .bind(&[Key::Char('z'), Key::Char('c')], CloseFold)
+            .bind(&[Key::Char('z'), Key::Char('R')], OpenAllFolds)
+            .bind(&[Key::Char('z'), Key::Char('M')], CloseAllFolds)
+            .bind_all(&[Key::Char('V')], ToggleSelection)
+            .bind_all(&[Key::Esc], Cancel)
+    }
+
+    /// Bind the given sequence of keys to an action, replacing any previous binding of the
+    /// sequence.
+    ///
+    /// Note that a sequence that is a prefix of another one (e.g., `g` and `g g`) shadows the
+    /// longer sequence.
+    pub fn bind(mut self, keys: &[Key], action: PagerAction) -> Self {
+        self.unbind(keys);
+        self.bindings.push((keys.to_vec(), action));
+        self
+    }
+
+    /// Bind each of the given keys to an action (see `bind`).
+    pub fn bind_all(self, keys: &[Key], action: PagerAction) -> Self {
+        keys.iter()
+            .fold(self, |bindings, &key| bindings.bind(&[key], action))
+    }
+
+    /// Remove the binding of the given sequence of keys, if any.
+    pub fn unbind(&mut self, keys: &[Key]) {
+        self.bindings.retain(|(k, _)| &k[..] != keys);
+    }
+
+    /// Get the action that the given sequence of keys is bound to, if any.
+    pub fn action_for(&self, keys: &[Key]) -> Option<PagerAction> {
+        self.bindings
+            .iter()
+            .find(|(k, _)| &k[..] == keys)
+            .map(|&(_, action)| action)
+    }
+
+    fn is_prefix(&self, keys: &[Key]) -> bool {
+        self.bindings
+            .iter()
+            .any(|(k, _)| k.len() > keys.len() && k.starts_with(keys))
+    }
+}
+
+/// What the next key is used for, if not for looking up a binding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Pending {
+    Nothing,
+    SetMark,
+    GoToMark,
+    Search { forwards: bool, pattern: String },
+}
+
+/// State of a `PagerBehavior` that spans multiple inputs, like numeric prefixes or a search pattern
+/// that is being typed.
+///
+/// The state should be kept alongside the pager and passed to every `PagerBehavior`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PagerKeyState {
+    count: Option<usize>,
+    keys: Vec<Key>,
+    pending: Pending,
+    search_forwards: bool,
+    half_page_lines: Option<usize>,
+}
+
+impl Default for PagerKeyState {
+    fn default() -> Self {
+        PagerKeyState {
+            count: None,
+            keys: Vec::new(),
+            pending: Pending::Nothing,
+            search_forwards: true,
+            half_page_lines: None,
+        }
+    }
+}
+
+impl PagerKeyState {
+    /// Create a state without any pending input.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the count that was typed so far, if any.
+    pub fn count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// Get the search prompt (e.g., `/pattern`) if a search pattern is being typed.
+    pub fn prompt(&self) -> Option<String> {
+        match self.pending {
+            Pending::Search {
+                forwards,
+                ref pattern,
+            } => Some(format!("{}{}", if forwards { '/' } else { '?' }, pattern)),
+            _ => None,
+        }
+    }
+
+    /// Get the number of lines that half page actions move by, if it was set using a count.
+    pub fn half_page_lines(&self) -> Option<usize> {
+        self.half_page_lines
+    }
+
+    /// Forget all pending input.
+    pub fn reset(&mut self) {
+        self.count = None;
+        self.keys.clear();
+        self.pending = Pending::Nothing;
+    }
+}
+
+/// Operates a `Pager` using the given key bindings when chained to an `Input`.
+///
+/// Inputs that are not keys or are not bound (and not part of pending input) are passed on.
+pub struct PagerBehavior<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    pager: &'a mut Pager<L, D>,
+    bindings: &'a PagerKeyBindings,
+    state: &'a mut PagerKeyState,
+}
+
+impl<'a, L, D> PagerBehavior<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    /// Create a behavior that operates `pager` using `bindings` and keeps its state in `state`.
+    pub fn new(
+        pager: &'a mut Pager<L, D>,
+        bindings: &'a PagerKeyBindings,
+        state: &'a mut PagerKeyState,
+    ) -> Self {
+        PagerBehavior {
+            pager,
+            bindings,
+            state,
+        }
+    }
+
+    /// Handle a key. Returns whether the key was consumed.
+    fn handle_key(&mut self, key: Key) -> bool {
+        match ::std::mem::replace(&mut self.state.pending, Pending::Nothing) {
+            Pending::Nothing => {}
+            Pending::SetMark => {
+                if let Key::Char(name) = key {
+                    self.pager.set_mark(name);
+                }
+                return true;
+            }
+            Pending::GoToMark => {
+                match key {
+                    Key::Char('\'') => {
+                        let _ = self.pager.jump_back();
+                    }
+                    Key::Char(name) => {
+                        let _ = self.pager.go_to_mark(name);
+                    }
+                    _ => {}
+                }
+                return true;
+            }
+            Pending::Search {
+                forwards,
+                mut pattern,
+            } => {
+                match key {
+                    Key::Char('\n') => self.search(forwards, &pattern),
+                    Key::Char(c) => {
+                        pattern.push(c);
+                        self.state.pending = Pending::Search { forwards, pattern };
+                    }
+                    Key::Backspace if !pattern.is_empty() => {
+                        pattern.pop();
+                        self.state.pending = Pending::Search { forwards, pattern };
+                    }
+                    _ => {}
+                }
+                return true;
+            }
+        }
+
+        if let Key::Char(digit) = key {
+            // A leading '0' is not part of a count, so that it can be bound.
+            if let Some(digit) = digit.to_digit(10) {
+                if self.state.keys.is_empty() && (digit != 0 || self.state.count.is_some()) {
+                    let count = self.state.count.unwrap_or(0);
+                    self.state.count =
+                        Some(count.saturating_mul(10).saturating_add(digit as usize));
+                    return true;
+                }
+            }
+        }
+
+        self.state.keys.push(key);
+        if let Some(action) = self.bindings.action_for(&self.state.keys) {
+            self.state.keys.clear();
+            let count = self.state.count.take();
+            self.execute(action, count);
+            true
+        } else if self.bindings.is_prefix(&self.state.keys) {
+            true
+        } else {
+            // Unbound keys cancel pending input, but are only consumed if they are part of a
+            // sequence.
+            let consumed = self.state.keys.len() > 1;
+            self.state.reset();
+            consumed
+        }
+    }
+
+    fn search(&mut self, forwards: bool, pattern: &str) {
+        // An empty pattern repeats the last search (in the new direction).
+        if !pattern.is_empty() && self.pager.set_search_pattern(pattern).is_err() {
+            return;
+        }
+        self.state.search_forwards = forwards;
+        let _ = if forwards {
+            self.pager.search_next()
+        } else {
+            self.pager.search_previous()
+        };
+    }
+
+    fn execute(&mut self, action: PagerAction, count: Option<usize>) {
+        use self::PagerAction::*;
+        let repetitions = count.unwrap_or(1);
+        let pager = &mut *self.pager;
+        let repeat = |pager: &mut Pager<L, D>, f: fn(&mut Pager<L, D>) -> OperationResult| {
+            for _ in 0..repetitions {
+                if f(pager).is_err() {
+                    break;
+                }
+            }
+        };
+        let go_to_count = |pager: &mut Pager<L, D>, count: usize| {
+            let _ = pager.go_to_line(LineIndex::new(count.saturating_sub(1)));
+        };
+        match action {
+            LineForwards => {
+                let _ = pager.scroll_forwards_by(repetitions);
+            }
+            LineBackwards => {
+                let _ = pager.scroll_backwards_by(repetitions);
+            }
+            PageForwards => {
+                let _ = match count {
+                    Some(count) => pager.scroll_forwards_by(count),
+                    None => pager.scroll_page_forwards(),
+                };
+            }
+            PageBackwards => {
+                let _ = match count {
+                    Some(count) => pager.scroll_backwards_by(count),
+                    None => pager.scroll_page_backwards(),
+                };
+            }
+            HalfPageForwards | HalfPageBackwards => {
+                if count.is_some() {
+                    self.state.half_page_lines = count;
+                }
+                let forwards = action == HalfPageForwards;
+                let _ = match (self.state.half_page_lines, forwards) {
+                    (Some(lines), true) => pager.scroll_forwards_by(lines),
+                    (Some(lines), false) => pager.scroll_backwards_by(lines),
+                    (None, true) => pager.scroll_half_page_forwards(),
+                    (None, false) => pager.scroll_half_page_backwards(),
+                };
+            }
+            GoToBeginning => match count {
+                Some(count) => go_to_count(pager, count),
+                None => {
+                    let _ = pager.scroll_to_beginning();
+                }
+            },
+            GoToEnd => match count {
+                Some(count) => go_to_count(pager, count),
+                None => {
+                    let _ = pager.scroll_to_end();
+                }
+            },
+            ScrollLeft => repeat(pager, Pager::scroll_left_half_screen),
+            ScrollRight => repeat(pager, Pager::scroll_right_half_screen),
+            CursorLeft => repeat(pager, Pager::cursor_left),
+            CursorRight => repeat(pager, Pager::cursor_right),
+            CursorWordForwards => repeat(pager, Pager::cursor_word_forwards),
+            CursorWordBackwards => repeat(pager, Pager::cursor_word_backwards),
+            CursorLineStart => {
+                let _ = pager.cursor_to_line_start();
+            }
+            CursorLineEnd => {
+                let _ = pager.cursor_to_line_end();
+            }
+            SearchForwards | SearchBackwards => {
+                self.state.pending = Pending::Search {
+                    forwards: action == SearchForwards,
+                    pattern: String::new(),
+                };
+            }
+            NextMatch | PreviousMatch => {
+                let forwards = self.state.search_forwards == (action == NextMatch);
+                for _ in 0..repetitions {
+                    let result = if forwards {
+                        pager.search_next()
+                    } else {
+                        pager.search_previous()
+                    };
+                    if result.is_err() {
+                        break;
+                    }
+                }
+            }
+            ToggleFollowMode => {
+                let enabled = pager.follow_mode();
+                pager.set_follow_mode(!enabled);
+            }
+            SetMark => self.state.pending = Pending::SetMark,
+            GoToMark => self.state.pending = Pending::GoToMark,
+            JumpBack => {
+                for _ in 0..repetitions {
+                    if pager.jump_back().is_err() {
+                        break;
+                    }
+                }
+            }
+            JumpForward => {
+                for _ in 0..repetitions {
+                    if pager.jump_forward().is_err() {
+                        break;
+                    }
+                }
+            }
+            ToggleFold => {
+                let _ = pager.toggle_fold();
+            }
+            OpenFold => {
+                let _ = pager.open_fold();
+            }
+            CloseFold => {
+                let _ = pager.close_fold();
+            }
+            OpenAllFolds => {
+                let _ = pager.open_all_folds();
+            }
+            CloseAllFolds => {
+                let _ = pager.close_all_folds();
+            }
+            ToggleSelection => {
+                if pager.has_selection() {
+                    pager.clear_selection();
+                } else {
+                    pager.start_selection();
+                }
+            }
+            Cancel => pager.clear_selection(),
+        }
+    }
+}
+
+impl<'a, L, D> Behavior for PagerBehavior<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    fn input(mut self, input: Input) -> Option<Input> {
+        let consumed = match input.event {
+            Event::Key(key) => self.handle_key(key),
+            _ => false,
+        };
+        if consumed {
+            None
+        } else {
+            Some(input)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PagerContent;
+
+    struct Fixture {
+        pager: Pager<String>,
+        bindings: PagerKeyBindings,
+        state: PagerKeyState,
+    }
+
+    impl Fixture {
+        fn new(bindings: PagerKeyBindings) -> Self {
+            let lines = (0..100).map(|i| format!("line {}", i)).collect::<Vec<_>>();
+            let mut pager = Pager::new();
+            pager.load(PagerContent::from_lines(lines));
+            Fixture {
+                pager,
+                bindings,
+                state: PagerKeyState::new(),
+            }
+        }
+
+        /// Press the keys typed in `keys` and return whether the last one was consumed.
+        fn type_keys(&mut self, keys: &str) -> bool {
+            keys.chars()
+                .map(|c| self.press(Key::Char(c)))
+                .last()
+                .unwrap()
+        }
+
+        fn press(&mut self, key: Key) -> bool {
+            let input = Input {
+                event: Event::Key(key),
+                raw: Vec::new(),
+            };
+            PagerBehavior::new(&mut self.pager, &self.bindings, &mut self.state)
+                .input(input)
+                .is_none()
+        }
+
+        fn line(&self) -> usize {
+            self.pager.current_line_index().raw_value()
+        }
+    }
+
+    #[test]
+    fn count_goes_to_line() {
+        let mut f = Fixture::new(PagerKeyBindings::less());
+        assert!(f.type_keys("42"));
+        assert_eq!(f.state.count(), Some(42));
+        assert!(f.type_keys("g"));
+        assert_eq!(f.line(), 41);
+        assert_eq!(f.state.count(), None);
+        f.type_keys("G");
+        assert_eq!(f.line(), 99);
+        f.type_keys("7G");
+        assert_eq!(f.line(), 6);
+        f.type_keys("g");
+        assert_eq!(f.line(), 0);
+    }
+
+    #[test]
+    fn key_sequences() {
+        let mut f = Fixture::new(PagerKeyBindings::vim());
+        f.type_keys("50G");
+        assert!(f.type_keys("g"));
+        assert_eq!(f.line(), 49);
+        assert!(f.type_keys("g"));
+        assert_eq!(f.line(), 0);
+        f.type_keys("10gg");
+        assert_eq!(f.line(), 9);
+    }
+
+    #[test]
+    fn z_prefix() {
+        let mut f = Fixture::new(PagerKeyBindings::vim());
+        f.pager
+            .content_mut()
+            .unwrap()
+            .folds_mut()
+            .add(LineIndex::new(1), LineIndex::new(9));
+        assert!(f.type_keys("z"));
+        assert!(!f
+            .pager
+            .content()
+            .unwrap()
+            .folds()
+            .is_hidden(LineIndex::new(2)));
+        assert!(f.type_keys("M"));
+        assert!(f
+            .pager
+            .content()
+            .unwrap()
+            .folds()
+            .is_hidden(LineIndex::new(2)));
+        f.type_keys("zR");
+        assert!(!f
+            .pager
+            .content()
+            .unwrap()
+            .folds()
+            .is_hidden(LineIndex::new(2)));
+
+        // An unbound sequence is consumed, but does nothing.
+        assert!(f.type_keys("zx"));
+        assert_eq!(f.line(), 0);
+        f.type_keys("j");
+        assert_eq!(f.line(), 1);
+    }
+
+    #[test]
+    fn unbound_keys_cancel_count() {
+        let mut f = Fixture::new(PagerKeyBindings::vim());
+        assert!(!f.type_keys("4x"));
+        assert_eq!(f.state.count(), None);
+        f.type_keys("j");
+        assert_eq!(f.line(), 1);
+        // A leading '0' is bound instead of being part of a count.
+        f.type_keys("0");
+        assert_eq!(f.state.count(), None);
+        f.type_keys("10j");
+        assert_eq!(f.line(), 11);
+    }
+
+    #[test]
+    fn page_counts_scroll_lines() {
+        let mut f = Fixture::new(PagerKeyBindings::less());
+        f.type_keys("5 ");
+        assert_eq!(f.line(), 5);
+        f.type_keys("3b");
+        assert_eq!(f.line(), 2);
+    }
+
+    #[test]
+    fn half_page_count_is_kept() {
+        let mut f = Fixture::new(PagerKeyBindings::less());
+        f.type_keys("5d");
+        assert_eq!(f.line(), 5);
+        assert_eq!(f.state.half_page_lines(), Some(5));
+        f.type_keys("d");
+        assert_eq!(f.line(), 10);
+        f.type_keys("u");
+        assert_eq!(f.line(), 5);
+        f.type_keys("2u");
+        assert_eq!(f.line(), 3);
+        f.type_keys("d");
+        assert_eq!(f.line(), 5);
+    }
+
+    #[test]
+    fn toggling_follow_mode_does_not_record_jumps() {
+        let mut f = Fixture::new(PagerKeyBindings::less());
+        f.type_keys("FFF");
+        assert!(f.pager.follow_mode());
+        assert_eq!(f.line(), 99);
+        assert!(f.pager.jump_back().is_err());
+    }
+}
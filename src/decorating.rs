@@ -1,18 +1,25 @@
 /// Types related to decoration of individual pager lines with, for example, line numbers.
 ///
 /// Implement `LineDecorator` for custom decoration, use `NoDecorator` if you do not want
-/// decoration at all or `LineNumberDecorator` for plain old line numbers.
+/// decoration at all or `LineNumberDecorator` for (absolute or relative) line numbers.
+/// `SignDecorator` draws markers (e.g., breakpoints) next to individual lines. Multiple decorators
+/// can be displayed next to each other using `DecoratorStack`.
 use unsegen::base::basic_types::*;
-use unsegen::base::{Cursor, Window};
-use unsegen::widget::{text_width, ColDemand, Demand};
+use unsegen::base::{Cursor, StyleModifier, Window};
+use unsegen::input::MouseButton;
+use unsegen::widget::{layout_linearly, text_width, ColDemand, Demand};
 
 use super::PagerLine;
 
+use std::cell::Cell;
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+
 /// Interface for anything that is able to decorate lines, i.e., to draw something next to the left
 /// of a pager line, given some information about the line.
 pub trait LineDecorator {
     /// The type of line that can be decorated using this implementation.
-    type Line: PagerLine;
+    type Line: PagerLine + ?Sized;
 
     /// Define how much (horizontal) space is required to draw the decoration of the given lines.
     ///
@@ -30,6 +37,9 @@ pub trait LineDecorator {
     /// Information of how to decorate the line can retrieved from the line itself, its index,
     /// and/or the index of the currently active line of the pager.
     ///
+    /// The window covers all visible rows of the line. If the line is wrapped, `wrap_info`
+    /// specifies which of the rows are continuation rows, see `WrapInfo`.
+    ///
     /// Note that window is at least one row in height and in fact is so in most cases, but an
     /// implementer cannot rely on that. It is also not guaranteed that the window is as wide as
     /// specified in the last call to `horizontal_space_demand`.
@@ -38,19 +48,63 @@ pub trait LineDecorator {
         line: &Self::Line,
         line_to_decorate_index: LineIndex,
         active_line_index: LineIndex,
+        wrap_info: WrapInfo,
         window: Window,
     );
+
+    /// React to a click on the decoration of the given line (e.g., to toggle a breakpoint).
+    ///
+    /// `column` is relative to the left border of the decoration, which was `width` columns wide
+    /// when it was last drawn. The default implementation does nothing.
+    fn click(
+        &mut self,
+        _line: &Self::Line,
+        _line_index: LineIndex,
+        _column: ColIndex,
+        _width: Width,
+        _button: MouseButton,
+    ) {
+    }
+}
+
+/// Information about the rows a (possibly wrapped) line occupies, passed to
+/// `LineDecorator::decorate`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WrapInfo {
+    /// The total number of rows of the line (1 if it is not wrapped).
+    pub num_rows: usize,
+    /// The row of the line that is drawn in the first row of the decorator window. This is not 0
+    /// if the beginning of the line is scrolled out of view.
+    pub first_visible_row: usize,
+}
+
+impl WrapInfo {
+    /// Get the row of the decorator window in which the first row of the line is drawn, if it is
+    /// visible.
+    pub fn first_row_in_window(&self) -> Option<RowIndex> {
+        if self.first_visible_row == 0 {
+            Some(RowIndex::new(0))
+        } else {
+            None
+        }
+    }
+
+    /// Check whether the given row of the decorator window is a continuation row, i.e., not the
+    /// first row of the line.
+    pub fn is_continuation_row(&self, window_row: RowIndex) -> bool {
+        self.first_visible_row as i32 + window_row.raw_value() > 0
+    }
 }
 
 /// Do not draw line decoration.
 ///
 /// This is the default for newly created `PagerContent`. Use `with_decorator` to specify another
 /// `LineDecorator`.
-pub struct NoDecorator<L> {
+pub struct NoDecorator<L: ?Sized> {
     _dummy: ::std::marker::PhantomData<L>,
 }
 
-impl<L> Default for NoDecorator<L> {
+impl<L: ?Sized> Default for NoDecorator<L> {
     fn default() -> Self {
         NoDecorator {
             _dummy: Default::default(),
@@ -58,7 +112,7 @@ impl<L> Default for NoDecorator<L> {
     }
 }
 
-impl<L: PagerLine> LineDecorator for NoDecorator<L> {
+impl<L: PagerLine + ?Sized> LineDecorator for NoDecorator<L> {
     type Line = L;
     fn horizontal_space_demand<'a, 'b: 'a>(
         &'a self,
@@ -69,25 +123,110 @@ impl<L: PagerLine> LineDecorator for NoDecorator<L> {
     {
         Demand::exact(0)
     }
-    fn decorate(&self, _: &L, _: LineIndex, _: LineIndex, _: Window) {}
+    fn decorate(&self, _: &L, _: LineIndex, _: LineIndex, _: WrapInfo, _: Window) {}
+}
+
+/// Specifies which numbers a `LineNumberDecorator` draws.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineNumberMode {
+    /// The number of each line.
+    Absolute,
+    /// The distance of each line to the active line.
+    Relative,
+    /// The number of the active line and the distance to the active line for all other lines.
+    Hybrid,
+}
+
+/// Specifies how line numbers are aligned within the column of a `LineNumberDecorator`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineNumberAlignment {
+    Left,
+    Right,
 }
 
 /// Draw line numbers next to every line.
 ///
+/// By default, absolute (1-based), right-aligned line numbers are drawn. Use the `with_*` methods
+/// to configure relative numbering, the number of the first line, the alignment, the separator
+/// between the numbers and the content, a marker for continuation rows of wrapped lines and the
+/// style of the active line's number.
+///
 /// Add to `PagerContent` using `with_decorator`.
-pub struct LineNumberDecorator<L> {
+pub struct LineNumberDecorator<L: ?Sized> {
+    mode: LineNumberMode,
+    first_line_number: usize,
+    alignment: LineNumberAlignment,
+    separator: String,
+    wrap_marker: Option<String>,
+    active_line_style: Option<StyleModifier>,
     _dummy: ::std::marker::PhantomData<L>,
 }
 
-impl<L> Default for LineNumberDecorator<L> {
+impl<L: ?Sized> Default for LineNumberDecorator<L> {
     fn default() -> Self {
         LineNumberDecorator {
+            mode: LineNumberMode::Absolute,
+            first_line_number: 1,
+            alignment: LineNumberAlignment::Right,
+            separator: " ".to_owned(),
+            wrap_marker: None,
+            active_line_style: None,
             _dummy: Default::default(),
         }
     }
 }
 
-impl<L: PagerLine> LineDecorator for LineNumberDecorator<L> {
+impl<L: ?Sized> LineNumberDecorator<L> {
+    /// Set which numbers are drawn (see `LineNumberMode`).
+    pub fn with_mode(mut self, mode: LineNumberMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the number of the first line of the content (1 by default).
+    pub fn with_first_line_number(mut self, first_line_number: usize) -> Self {
+        self.first_line_number = first_line_number;
+        self
+    }
+
+    /// Set how numbers are aligned within the column.
+    pub fn with_alignment(mut self, alignment: LineNumberAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Set the text drawn between the numbers and the content (a single space by default).
+    pub fn with_separator<S: Into<String>>(mut self, separator: S) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Set a marker (e.g., "↪") that is drawn in the continuation rows of wrapped lines instead
+    /// of leaving them empty.
+    pub fn with_wrap_marker<S: Into<String>>(mut self, marker: S) -> Self {
+        self.wrap_marker = Some(marker.into());
+        self
+    }
+
+    /// Set the style of the number of the active line.
+    pub fn with_active_line_style(mut self, style: StyleModifier) -> Self {
+        self.active_line_style = Some(style);
+        self
+    }
+
+    fn number_for_line(&self, line: LineIndex, active_line: LineIndex) -> usize {
+        let (line, active_line) = (line.raw_value(), active_line.raw_value());
+        let distance = max(line, active_line) - min(line, active_line);
+        match self.mode {
+            LineNumberMode::Absolute => line + self.first_line_number,
+            LineNumberMode::Relative => distance,
+            LineNumberMode::Hybrid if distance == 0 => line + self.first_line_number,
+            LineNumberMode::Hybrid => distance,
+        }
+    }
+}
+
+impl<L: PagerLine + ?Sized> LineDecorator for LineNumberDecorator<L> {
     type Line = L;
     fn horizontal_space_demand<'a, 'b: 'a>(
         &'a self,
@@ -96,18 +235,324 @@ impl<L: PagerLine> LineDecorator for LineNumberDecorator<L> {
     where
         Self::Line: 'b,
     {
+        // Relative numbers never exceed the absolute number of the last line, so this also avoids
+        // changing the width when the active line is moved.
         let max_space = lines
             .last()
-            .map(|(i, _)| text_width(format!(" {} ", i).as_str()))
+            .map(|(i, _)| {
+                let number = i.raw_value() + self.first_line_number;
+                text_width(format!(" {}{}", number, self.separator).as_str())
+            })
             .unwrap_or_else(|| Width::new(0).unwrap());
         Demand::exact(max_space)
     }
-    fn decorate(&self, _: &L, line_to_decorate_index: LineIndex, _: LineIndex, mut window: Window) {
-        let width = (window.get_width() - 2).positive_or_zero();
-        let line_number = LineNumber::from(line_to_decorate_index);
-        let mut cursor = Cursor::new(&mut window).position(ColIndex::new(0), RowIndex::new(0));
+    fn decorate(
+        &self,
+        _: &L,
+        line_to_decorate_index: LineIndex,
+        active_line_index: LineIndex,
+        wrap_info: WrapInfo,
+        mut window: Window,
+    ) {
+        let separator_width = text_width(&self.separator).raw_value();
+        let width: usize = (window.get_width() - (1 + separator_width))
+            .positive_or_zero()
+            .into();
+        let num_rows: usize = window.get_height().into();
+        let mut cursor = Cursor::new(&mut window);
+
+        if line_to_decorate_index == active_line_index {
+            if let Some(style) = self.active_line_style {
+                cursor.set_style_modifier(style);
+            }
+        }
+
+        for row in (0..num_rows).map(|r| RowIndex::new(r as i32)) {
+            let text = if !wrap_info.is_continuation_row(row) {
+                self.number_for_line(line_to_decorate_index, active_line_index)
+                    .to_string()
+            } else if let Some(ref marker) = self.wrap_marker {
+                marker.clone()
+            } else {
+                continue;
+            };
+            cursor.move_to(ColIndex::new(0), row);
+
+            use std::fmt::Write;
+            match self.alignment {
+                LineNumberAlignment::Left => {
+                    write!(cursor, " {:<width$}{}", text, self.separator, width = width)
+                }
+                LineNumberAlignment::Right => {
+                    write!(cursor, " {:>width$}{}", text, self.separator, width = width)
+                }
+            }
+            .unwrap();
+        }
+    }
+}
+
+/// A marker that can be drawn next to a line using a `SignDecorator`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sign {
+    /// The text of the sign, which should be (at most) as wide as the sign column.
+    pub glyph: String,
+    /// The style the glyph is drawn with.
+    pub style: StyleModifier,
+    /// If there are multiple signs for a line, only the one with the highest priority is drawn.
+    pub priority: i32,
+}
+
+impl Sign {
+    /// Create a sign with the given glyph and style and a priority of 0.
+    pub fn new<S: Into<String>>(glyph: S, style: StyleModifier) -> Self {
+        Sign {
+            glyph: glyph.into(),
+            style,
+            priority: 0,
+        }
+    }
+
+    /// Set the priority of the sign.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Draw signs (e.g., breakpoint markers, an arrow for the current position of a debugger or
+/// diagnostics) next to lines.
+///
+/// Each line can have multiple signs, which are identified by a `kind` (e.g., "breakpoint"), so
+/// that they can be updated independently. Of all signs of a line, only the one with the highest
+/// priority is drawn. Use `PagerContent::decorator_mut` to update signs of a loaded content.
+///
+/// Note that signs are attached to line indices, they are not moved if lines of the content are
+/// inserted or removed.
+pub struct SignDecorator<L: ?Sized> {
+    signs: BTreeMap<usize, Vec<(String, Sign)>>,
+    width: usize,
+    _dummy: ::std::marker::PhantomData<L>,
+}
+
+impl<L: ?Sized> Default for SignDecorator<L> {
+    fn default() -> Self {
+        SignDecorator {
+            signs: BTreeMap::new(),
+            width: 2,
+            _dummy: Default::default(),
+        }
+    }
+}
+
+impl<L: ?Sized> SignDecorator<L> {
+    /// Set the width of the sign column (2 by default).
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Place a sign of the given kind next to a line, replacing any previous sign of the same kind
+    /// at that line.
+    pub fn place<I: Into<LineIndex>, K: Into<String>>(&mut self, line: I, kind: K, sign: Sign) {
+        let kind = kind.into();
+        let signs = self.signs.entry(line.into().raw_value()).or_default();
+        signs.retain(|(k, _)| *k != kind);
+        signs.push((kind, sign));
+    }
+
+    /// Remove the sign of the given kind from a line, if present.
+    pub fn remove<I: Into<LineIndex>>(&mut self, line: I, kind: &str) {
+        let line = line.into().raw_value();
+        let now_empty = if let Some(signs) = self.signs.get_mut(&line) {
+            signs.retain(|(k, _)| k != kind);
+            signs.is_empty()
+        } else {
+            false
+        };
+        if now_empty {
+            self.signs.remove(&line);
+        }
+    }
+
+    /// Remove all signs of the given kind from all lines.
+    pub fn remove_kind(&mut self, kind: &str) {
+        for signs in self.signs.values_mut() {
+            signs.retain(|(k, _)| k != kind);
+        }
+        self.signs.retain(|_, signs| !signs.is_empty());
+    }
 
-        use std::fmt::Write;
-        write!(cursor, " {:width$} ", line_number, width = width.into()).unwrap();
+    /// Remove all signs from all lines.
+    pub fn clear(&mut self) {
+        self.signs.clear();
+    }
+
+    /// Get the sign that is drawn next to the given line (i.e., the one with the highest
+    /// priority), if any.
+    pub fn sign_for_line<I: Into<LineIndex>>(&self, line: I) -> Option<&Sign> {
+        self.signs.get(&line.into().raw_value()).and_then(|signs| {
+            signs
+                .iter()
+                .map(|(_, sign)| sign)
+                .max_by_key(|s| s.priority)
+        })
+    }
+
+    /// Iterate over all lines that have a sign along with the sign that is drawn next to them.
+    pub fn signs(&self) -> impl Iterator<Item = (LineIndex, &Sign)> {
+        self.signs.iter().filter_map(|(&line, signs)| {
+            signs
+                .iter()
+                .map(|(_, sign)| sign)
+                .max_by_key(|s| s.priority)
+                .map(|sign| (LineIndex::new(line), sign))
+        })
+    }
+}
+
+impl<L: PagerLine + ?Sized> LineDecorator for SignDecorator<L> {
+    type Line = L;
+    fn horizontal_space_demand<'a, 'b: 'a>(
+        &'a self,
+        _: impl DoubleEndedIterator<Item = (LineIndex, &'b Self::Line)> + 'b,
+    ) -> ColDemand
+    where
+        Self::Line: 'b,
+    {
+        Demand::exact(Width::new(self.width as i32).unwrap())
+    }
+    fn decorate(
+        &self,
+        _: &L,
+        line_to_decorate_index: LineIndex,
+        _: LineIndex,
+        wrap_info: WrapInfo,
+        mut window: Window,
+    ) {
+        // Signs are only drawn next to the first row of a line.
+        if let (Some(sign), Some(row)) = (
+            self.sign_for_line(line_to_decorate_index),
+            wrap_info.first_row_in_window(),
+        ) {
+            let mut cursor = Cursor::new(&mut window).position(ColIndex::new(0), row);
+            cursor.set_style_modifier(sign.style);
+            cursor.write(&sign.glyph);
+        }
+    }
+}
+
+/// Draw two decorators next to each other, `left` to the left of `right`.
+///
+/// The space demand of the stack is the sum of the demands of both decorators. Stacks can be
+/// nested to combine more than two decorators:
+///
+/// ```ignore
+/// DecoratorStack::new(LineNumberDecorator::default(), DecoratorStack::new(a, b))
+/// ```
+pub struct DecoratorStack<A: LineDecorator, B: LineDecorator<Line = A::Line>> {
+    left: A,
+    right: B,
+    /// Demands of both decorators as of the last call to `horizontal_space_demand`.
+    demands: Cell<(ColDemand, ColDemand)>,
+}
+
+impl<A: LineDecorator, B: LineDecorator<Line = A::Line>> DecoratorStack<A, B> {
+    /// Create a stack of the two specified decorators.
+    pub fn new(left: A, right: B) -> Self {
+        DecoratorStack {
+            left,
+            right,
+            demands: Cell::new((Demand::exact(0), Demand::exact(0))),
+        }
+    }
+
+    /// Get a reference to the left decorator.
+    pub fn left(&self) -> &A {
+        &self.left
+    }
+
+    /// Get a mutable reference to the left decorator.
+    pub fn left_mut(&mut self) -> &mut A {
+        &mut self.left
+    }
+
+    /// Get a reference to the right decorator.
+    pub fn right(&self) -> &B {
+        &self.right
+    }
+
+    /// Get a mutable reference to the right decorator.
+    pub fn right_mut(&mut self) -> &mut B {
+        &mut self.right
+    }
+}
+
+impl<A: LineDecorator, B: LineDecorator<Line = A::Line>> LineDecorator for DecoratorStack<A, B> {
+    type Line = A::Line;
+    fn horizontal_space_demand<'a, 'b: 'a>(
+        &'a self,
+        lines: impl DoubleEndedIterator<Item = (LineIndex, &'b Self::Line)> + 'b,
+    ) -> ColDemand
+    where
+        Self::Line: 'b,
+    {
+        let lines = lines.collect::<Vec<_>>();
+        let left = self.left.horizontal_space_demand(lines.clone().into_iter());
+        let right = self.right.horizontal_space_demand(lines.into_iter());
+        self.demands.set((left, right));
+        left + right
+    }
+    fn decorate(
+        &self,
+        line: &Self::Line,
+        line_to_decorate_index: LineIndex,
+        active_line_index: LineIndex,
+        wrap_info: WrapInfo,
+        window: Window,
+    ) {
+        let (left, right) = self.demands.get();
+        let split_pos = layout_linearly(
+            window.get_width(),
+            Width::new(0).unwrap(),
+            &[left, right],
+            &[1.0, 1.0],
+        )[0];
+        let (left_window, right_window) = window
+            .split(split_pos.from_origin())
+            .expect("valid split pos");
+        self.left.decorate(
+            line,
+            line_to_decorate_index,
+            active_line_index,
+            wrap_info,
+            left_window,
+        );
+        self.right.decorate(
+            line,
+            line_to_decorate_index,
+            active_line_index,
+            wrap_info,
+            right_window,
+        );
+    }
+    fn click(
+        &mut self,
+        line: &Self::Line,
+        line_index: LineIndex,
+        column: ColIndex,
+        width: Width,
+        button: MouseButton,
+    ) {
+        let (left, right) = self.demands.get();
+        let split_pos =
+            layout_linearly(width, Width::new(0).unwrap(), &[left, right], &[1.0, 1.0])[0];
+        if column < split_pos.from_origin() {
+            self.left.click(line, line_index, column, split_pos, button);
+        } else {
+            let right_width = (width - split_pos).positive_or_zero();
+            self.right
+                .click(line, line_index, column - split_pos, right_width, button);
+        }
     }
 }
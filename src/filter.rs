@@ -0,0 +1,228 @@
+/// Determining which lines of a `Pager` are displayed, considering closed folds and the filter.
+use unsegen::base::LineIndex;
+
+use super::{Folds, LineSource, PagerLine};
+
+use std::cell::{Cell, RefCell};
+use std::cmp::max;
+
+type Predicate<L> = Box<dyn Fn(LineIndex, &L) -> bool>;
+
+/// A predicate that decides which lines are displayed, along with the (lazily determined)
+/// matching lines.
+pub struct LineFilter<L: ?Sized> {
+    predicate: Predicate<L>,
+    pub enabled: bool,
+    /// Sorted indices of matching lines among the first `num_checked` lines of the content.
+    matches: RefCell<Vec<usize>>,
+    num_checked: Cell<usize>,
+}
+
+impl<L: PagerLine + ?Sized> LineFilter<L> {
+    pub fn new(predicate: Predicate<L>) -> Self {
+        LineFilter {
+            predicate,
+            enabled: true,
+            matches: RefCell::new(Vec::new()),
+            num_checked: Cell::new(0),
+        }
+    }
+
+    /// Forget which lines match after the lines starting at `first_changed_line` were modified.
+    pub fn lines_changed(&self, first_changed_line: usize) {
+        self.num_checked
+            .set(::std::cmp::min(self.num_checked.get(), first_changed_line));
+        self.matches
+            .borrow_mut()
+            .retain(|&line| line < first_changed_line);
+    }
+
+    /// Check lines until `line` has been checked or the end of the content has been reached.
+    /// Returns whether `line` exists.
+    fn check_until(&self, storage: &dyn LineSource<Line = L>, line: usize) -> bool {
+        let mut matches = self.matches.borrow_mut();
+        while self.num_checked.get() <= line {
+            let next = self.num_checked.get();
+            match storage.line(next) {
+                Some(content) => {
+                    if (self.predicate)(LineIndex::new(next), content) {
+                        matches.push(next);
+                    }
+                    self.num_checked.set(next + 1);
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn matches(&self, storage: &dyn LineSource<Line = L>, line: usize) -> bool {
+        self.check_until(storage, line) && self.matches.borrow().binary_search(&line).is_ok()
+    }
+
+    fn next_match_after(&self, storage: &dyn LineSource<Line = L>, line: usize) -> Option<usize> {
+        loop {
+            {
+                let matches = self.matches.borrow();
+                let pos = match matches.binary_search(&(line + 1)) {
+                    Ok(pos) | Err(pos) => pos,
+                };
+                if let Some(&found) = matches.get(pos) {
+                    return Some(found);
+                }
+            }
+            // Lines up to `line` may not have been checked yet, but cannot be the next match.
+            let next_unchecked = max(self.num_checked.get(), line + 1);
+            if !self.check_until(storage, next_unchecked) {
+                return None;
+            }
+        }
+    }
+
+    fn previous_match_before(
+        &self,
+        storage: &dyn LineSource<Line = L>,
+        line: usize,
+    ) -> Option<usize> {
+        if line == 0 {
+            return None;
+        }
+        self.check_until(storage, line - 1);
+        let matches = self.matches.borrow();
+        let pos = match matches.binary_search(&line) {
+            Ok(pos) | Err(pos) => pos,
+        };
+        pos.checked_sub(1).map(|pos| matches[pos])
+    }
+}
+
+/// Which lines of the content are displayed: All lines that are not hidden by closed folds and
+/// match the (enabled) filter.
+pub struct Visibility<'a, L: ?Sized + 'a> {
+    pub storage: &'a dyn LineSource<Line = L>,
+    pub folds: &'a Folds,
+    pub filter: Option<&'a LineFilter<L>>,
+}
+
+impl<'a, L: PagerLine + ?Sized + 'a> Visibility<'a, L> {
+    /// Check whether the given line matches the filter (if it is enabled).
+    pub fn passes_filter(&self, line: usize) -> bool {
+        match self.filter {
+            Some(filter) if filter.enabled => filter.matches(self.storage, line),
+            _ => true,
+        }
+    }
+
+    /// Check whether the given (existing) line is displayed.
+    pub fn is_visible(&self, line: usize) -> bool {
+        !self.folds.is_hidden(LineIndex::new(line)) && self.passes_filter(line)
+    }
+
+    /// Find the first displayed line after `line`.
+    pub fn next_visible(&self, line: usize) -> Option<usize> {
+        let mut line = line;
+        loop {
+            line = match self.filter {
+                Some(filter) if filter.enabled => filter.next_match_after(self.storage, line)?,
+                _ if self.storage.line(line + 1).is_some() => line + 1,
+                _ => return None,
+            };
+            match self.folds.hidden_range(line) {
+                Some(range) => line = range.end - 1,
+                None => return Some(line),
+            }
+        }
+    }
+
+    /// Find the last displayed line before `line`.
+    pub fn previous_visible(&self, line: usize) -> Option<usize> {
+        let mut line = line;
+        loop {
+            line = match self.filter {
+                Some(filter) if filter.enabled => {
+                    filter.previous_match_before(self.storage, line)?
+                }
+                _ => line.checked_sub(1)?,
+            };
+            match self.folds.hidden_range(line) {
+                Some(range) => line = range.start,
+                None => return Some(line),
+            }
+        }
+    }
+
+    /// Get the line `n` displayed lines after `line`, or the line after the last displayed line
+    /// if there are fewer displayed lines.
+    pub fn nth_visible_after(&self, line: usize, n: usize) -> usize {
+        let mut line = line;
+        for _ in 0..n {
+            match self.next_visible(line) {
+                Some(next) => line = next,
+                None => return line + 1,
+            }
+        }
+        line
+    }
+
+    /// Get the line `n` displayed lines before `line`, or the first displayed line if there are
+    /// fewer displayed lines.
+    pub fn nth_visible_before(&self, line: usize, n: usize) -> usize {
+        let mut line = line;
+        for _ in 0..n {
+            match self.previous_visible(line) {
+                Some(previous) => line = previous,
+                None => break,
+            }
+        }
+        line
+    }
+
+    /// Get the given line if it is displayed, or else the closest displayed line before (or, if
+    /// there is none, after) it.
+    pub fn closest_visible(&self, line: usize) -> usize {
+        if self.is_visible(line) {
+            line
+        } else {
+            self.previous_visible(line)
+                .or_else(|| self.next_visible(line))
+                .unwrap_or(line)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn even_lines() -> LineFilter<String> {
+        LineFilter::new(Box::new(|_, line: &String| {
+            line.parse::<usize>().unwrap() % 2 == 0
+        }))
+    }
+
+    #[test]
+    fn next_match_after_unchecked_lines() {
+        let lines = (0..10).map(|i| i.to_string()).collect::<Vec<_>>();
+        let filter = even_lines();
+        assert_eq!(filter.next_match_after(&lines, 5), Some(6));
+        assert_eq!(filter.next_match_after(&lines, 1), Some(2));
+        assert_eq!(filter.next_match_after(&lines, 8), None);
+
+        filter.lines_changed(3);
+        assert_eq!(filter.next_match_after(&lines, 6), Some(8));
+        assert_eq!(filter.next_match_after(&lines, 3), Some(4));
+        assert_eq!(filter.next_match_after(&lines, 20), None);
+    }
+
+    #[test]
+    fn previous_match_before_unchecked_lines() {
+        let lines = (0..10).map(|i| i.to_string()).collect::<Vec<_>>();
+        let filter = even_lines();
+        assert_eq!(filter.previous_match_before(&lines, 7), Some(6));
+        assert_eq!(filter.previous_match_before(&lines, 2), Some(0));
+        assert_eq!(filter.previous_match_before(&lines, 0), None);
+        assert!(filter.matches(&lines, 4));
+        assert!(!filter.matches(&lines, 9));
+        assert!(!filter.matches(&lines, 10));
+    }
+}
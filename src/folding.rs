@@ -0,0 +1,439 @@
+/// Types related to folding, i.e., collapsing ranges of lines of a `Pager` into a single line.
+///
+/// `Folds` stores the fold ranges of a `PagerContent`. They can be defined manually or using a
+/// `FoldProvider` such as `IndentationFolds` or `BraceFolds`.
+use unsegen::base::LineIndex;
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// A range of lines that can be collapsed into its first line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fold {
+    /// The first line of the fold, which stays visible if the fold is closed.
+    pub first: LineIndex,
+    /// The last line of the fold.
+    pub last: LineIndex,
+    /// Whether the fold is closed, i.e., all but the first line are hidden.
+    pub closed: bool,
+}
+
+impl Fold {
+    /// The number of lines of the fold (including the first one).
+    pub fn num_lines(&self) -> usize {
+        self.last.raw_value() - self.first.raw_value() + 1
+    }
+
+    fn contains(&self, line: usize) -> bool {
+        self.first.raw_value() <= line && line <= self.last.raw_value()
+    }
+}
+
+/// Interface for anything that determines foldable ranges of lines from the content.
+pub trait FoldProvider {
+    /// Find all foldable ranges in the given lines. Each range is specified by its first and last
+    /// line (inclusive).
+    ///
+    /// Ranges may be nested, but should not partially overlap.
+    fn find_folds(&self, lines: &mut dyn Iterator<Item = &str>) -> Vec<(usize, usize)>;
+}
+
+/// Determine folds from the indentation of lines (like vim's `foldmethod=indent`).
+///
+/// A fold starts at any line that is followed by lines that are more indented. Blank lines do not
+/// end a fold.
+pub struct IndentationFolds {
+    tab_width: usize,
+}
+
+impl Default for IndentationFolds {
+    fn default() -> Self {
+        IndentationFolds { tab_width: 4 }
+    }
+}
+
+impl IndentationFolds {
+    /// Set the number of columns a tab counts as (4 by default).
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    fn indentation(&self, line: &str) -> usize {
+        line.chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| if c == '\t' { self.tab_width } else { 1 })
+            .sum()
+    }
+}
+
+impl FoldProvider for IndentationFolds {
+    fn find_folds(&self, lines: &mut dyn Iterator<Item = &str>) -> Vec<(usize, usize)> {
+        let mut folds = Vec::new();
+        // Potential first lines of folds and their indentation.
+        let mut open: Vec<(usize, usize)> = Vec::new();
+        let mut last_non_blank = 0;
+        for (index, line) in lines.enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let indentation = self.indentation(line);
+            while let Some(&(first, first_indentation)) = open.last() {
+                if indentation > first_indentation {
+                    break;
+                }
+                open.pop();
+                if last_non_blank > first {
+                    folds.push((first, last_non_blank));
+                }
+            }
+            open.push((index, indentation));
+            last_non_blank = index;
+        }
+        for (first, _) in open.into_iter().rev() {
+            if last_non_blank > first {
+                folds.push((first, last_non_blank));
+            }
+        }
+        folds
+    }
+}
+
+/// Determine folds from matching pairs of braces (like vim's `foldmethod=marker` with `{` and
+/// `}` as markers).
+///
+/// A fold ranges from a line containing an opening brace to the line containing the matching
+/// closing brace. Note that braces within strings or comments are not treated specially.
+pub struct BraceFolds {
+    open: char,
+    close: char,
+}
+
+impl Default for BraceFolds {
+    fn default() -> Self {
+        BraceFolds {
+            open: '{',
+            close: '}',
+        }
+    }
+}
+
+impl BraceFolds {
+    /// Use the specified characters as opening and closing braces (`{` and `}` by default).
+    pub fn with_braces(mut self, open: char, close: char) -> Self {
+        self.open = open;
+        self.close = close;
+        self
+    }
+}
+
+impl FoldProvider for BraceFolds {
+    fn find_folds(&self, lines: &mut dyn Iterator<Item = &str>) -> Vec<(usize, usize)> {
+        let mut folds = Vec::new();
+        let mut open = Vec::new();
+        for (index, line) in lines.enumerate() {
+            for c in line.chars() {
+                if c == self.open {
+                    open.push(index);
+                } else if c == self.close {
+                    match open.pop() {
+                        Some(first) if first < index => folds.push((first, index)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        folds
+    }
+}
+
+/// The folds of a `PagerContent`.
+///
+/// There is at most one fold starting at each line. Folds are moved along with their lines if
+/// lines are inserted or removed using `PagerContent::replace_range` (or `Pager::replace_range`).
+/// Folds that overlap with replaced lines are adjusted (if they enclose the replaced lines) or
+/// removed.
+///
+/// Use `Pager::toggle_fold` and friends to open and close the fold at the active line of a
+/// `Pager`.
+#[derive(Clone, Debug, Default)]
+pub struct Folds {
+    /// Last line and state of all folds by their first line.
+    folds: BTreeMap<usize, (usize, bool)>,
+    /// Sorted, disjoint ranges of lines hidden by closed folds.
+    hidden: Vec<Range<usize>>,
+}
+
+impl Folds {
+    /// Determine all folds of the given lines using a `FoldProvider`. All folds are open.
+    pub fn from_provider<'a, P: FoldProvider, I: Iterator<Item = &'a str>>(
+        provider: &P,
+        mut lines: I,
+    ) -> Self {
+        let mut folds = Folds::default();
+        for (first, last) in provider.find_folds(&mut lines) {
+            folds.add(LineIndex::new(first), LineIndex::new(last));
+        }
+        folds
+    }
+
+    /// Define an (initially open) fold from `first` to `last` (inclusive).
+    ///
+    /// Folds must span at least two lines. If there already is a fold starting at `first`, only
+    /// the larger of both is kept.
+    pub fn add<I: Into<LineIndex>>(&mut self, first: I, last: I) {
+        let (first, last) = (first.into().raw_value(), last.into().raw_value());
+        if last <= first {
+            return;
+        }
+        let entry = self.folds.entry(first).or_insert((last, false));
+        if entry.0 < last {
+            entry.0 = last;
+        }
+        self.update_hidden();
+    }
+
+    /// Remove the fold starting at the given line, if present.
+    pub fn remove<I: Into<LineIndex>>(&mut self, first: I) {
+        self.folds.remove(&first.into().raw_value());
+        self.update_hidden();
+    }
+
+    /// Remove all folds.
+    pub fn clear(&mut self) {
+        self.folds.clear();
+        self.update_hidden();
+    }
+
+    /// Check whether there are no folds defined.
+    pub fn is_empty(&self) -> bool {
+        self.folds.is_empty()
+    }
+
+    /// Iterate over all folds, ordered by their first line.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = Fold> + 'a {
+        self.folds
+            .iter()
+            .map(|(&first, &(last, closed))| to_fold(first, last, closed))
+    }
+
+    /// Get the fold starting at the given line, if present.
+    pub fn fold_starting_at<I: Into<LineIndex>>(&self, line: I) -> Option<Fold> {
+        let first = line.into().raw_value();
+        self.folds
+            .get(&first)
+            .map(|&(last, closed)| to_fold(first, last, closed))
+    }
+
+    /// Get the innermost fold that contains the given line, if any.
+    pub fn innermost_fold<I: Into<LineIndex>>(&self, line: I) -> Option<Fold> {
+        let line = line.into().raw_value();
+        self.folds
+            .range(..=line)
+            .rev()
+            .map(|(&first, &(last, closed))| to_fold(first, last, closed))
+            .find(|fold| fold.contains(line))
+    }
+
+    /// Open or close the fold starting at the given line, if present.
+    pub fn set_closed<I: Into<LineIndex>>(&mut self, first: I, closed: bool) {
+        if let Some(fold) = self.folds.get_mut(&first.into().raw_value()) {
+            fold.1 = closed;
+        }
+        self.update_hidden();
+    }
+
+    /// Open all folds.
+    pub fn open_all(&mut self) {
+        self.set_all_closed(false);
+    }
+
+    /// Close all folds.
+    pub fn close_all(&mut self) {
+        self.set_all_closed(true);
+    }
+
+    fn set_all_closed(&mut self, closed: bool) {
+        for fold in self.folds.values_mut() {
+            fold.1 = closed;
+        }
+        self.update_hidden();
+    }
+
+    /// Open all folds that hide the given line.
+    pub fn reveal<I: Into<LineIndex>>(&mut self, line: I) {
+        let line = line.into().raw_value();
+        for (_, fold) in self.folds.range_mut(..line) {
+            if fold.0 >= line {
+                fold.1 = false;
+            }
+        }
+        self.update_hidden();
+    }
+
+    /// Check whether the given line is hidden by a closed fold.
+    pub fn is_hidden<I: Into<LineIndex>>(&self, line: I) -> bool {
+        self.hidden_range(line.into().raw_value()).is_some()
+    }
+
+    /// Get the range of lines hidden by closed folds that contains the given line, if any.
+    pub(crate) fn hidden_range(&self, line: usize) -> Option<Range<usize>> {
+        let candidates = match self.hidden.binary_search_by_key(&line, |range| range.start) {
+            Ok(i) => i + 1,
+            Err(i) => i,
+        };
+        self.hidden[..candidates]
+            .last()
+            .filter(|range| line < range.end)
+            .cloned()
+    }
+
+    fn update_hidden(&mut self) {
+        self.hidden.clear();
+        for (&first, &(last, closed)) in self.folds.iter() {
+            if !closed {
+                continue;
+            }
+            let range = first + 1..last + 1;
+            match self.hidden.last_mut() {
+                Some(previous) if range.start <= previous.end => {
+                    previous.end = ::std::cmp::max(previous.end, range.end);
+                }
+                _ => self.hidden.push(range),
+            }
+        }
+    }
+
+    /// Update the folds after the lines in `range` have been replaced by `num_new_lines` lines.
+    pub(crate) fn lines_replaced(&mut self, range: Range<usize>, num_new_lines: usize) {
+        let folds = ::std::mem::take(&mut self.folds);
+        for (first, (last, closed)) in folds {
+            let new_fold = if last < range.start {
+                Some((first, last))
+            } else if first >= range.end {
+                Some((
+                    first - range.len() + num_new_lines,
+                    last - range.len() + num_new_lines,
+                ))
+            } else if first < range.start && last >= range.end {
+                Some((first, last - range.len() + num_new_lines))
+            } else {
+                None
+            };
+            if let Some((first, last)) = new_fold {
+                if first < last {
+                    self.folds.insert(first, (last, closed));
+                }
+            }
+        }
+        self.update_hidden();
+    }
+}
+
+fn to_fold(first: usize, last: usize, closed: bool) -> Fold {
+    Fold {
+        first: LineIndex::new(first),
+        last: LineIndex::new(last),
+        closed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_folds<P: FoldProvider>(provider: &P, lines: &[&str]) -> Vec<(usize, usize)> {
+        let mut folds = provider.find_folds(&mut lines.iter().cloned());
+        folds.sort();
+        folds
+    }
+
+    #[test]
+    fn indentation_folds() {
+        let lines = [
+            "fn main() {",
+            "    if x {",
+            "        y();",
+            "",
+            "        z();",
+            "    }",
+            "}",
+            "top",
+            "    nested",
+        ];
+        assert_eq!(
+            find_folds(&IndentationFolds::default(), &lines),
+            vec![(0, 5), (1, 4), (7, 8)]
+        );
+    }
+
+    #[test]
+    fn indentation_folds_count_tabs() {
+        let lines = ["a", "\tb", "    c", "  d"];
+        assert_eq!(
+            find_folds(&IndentationFolds::default(), &lines),
+            vec![(0, 3)]
+        );
+        assert_eq!(
+            find_folds(&IndentationFolds::default().with_tab_width(2), &lines),
+            vec![(0, 3), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn brace_folds() {
+        let lines = ["fn main() {", "    if x { y(); }", "    [", "    ]", "}"];
+        assert_eq!(find_folds(&BraceFolds::default(), &lines), vec![(0, 4)]);
+        assert_eq!(
+            find_folds(&BraceFolds::default().with_braces('[', ']'), &lines),
+            vec![(2, 3)]
+        );
+    }
+
+    #[test]
+    fn unbalanced_brace_folds() {
+        let lines = ["}", "a {", "b {", "c", "}", "{", "}}"];
+        assert_eq!(
+            find_folds(&BraceFolds::default(), &lines),
+            vec![(1, 6), (2, 4), (5, 6)]
+        );
+        let lines = ["a {", "b {", "c }"];
+        assert_eq!(find_folds(&BraceFolds::default(), &lines), vec![(1, 2)]);
+    }
+
+    #[test]
+    fn closed_folds_hide_lines() {
+        let mut folds = Folds::from_provider(
+            &BraceFolds::default(),
+            ["a {", "b {", "c", "}", "d", "}", "e"].iter().cloned(),
+        );
+        folds.set_closed(LineIndex::new(1), true);
+        assert!(!folds.is_hidden(LineIndex::new(1)));
+        assert!(folds.is_hidden(LineIndex::new(2)));
+        assert!(folds.is_hidden(LineIndex::new(3)));
+        assert!(!folds.is_hidden(LineIndex::new(4)));
+
+        folds.close_all();
+        assert_eq!(folds.hidden_range(4), Some(1..6));
+        folds.reveal(LineIndex::new(4));
+        assert!(!folds.is_hidden(LineIndex::new(4)));
+        assert!(folds.is_hidden(LineIndex::new(2)));
+    }
+
+    #[test]
+    fn lines_replaced_moves_and_removes_folds() {
+        let mut folds = Folds::default();
+        folds.add(LineIndex::new(0), LineIndex::new(9));
+        folds.add(LineIndex::new(2), LineIndex::new(4));
+        folds.add(LineIndex::new(3), LineIndex::new(5));
+        folds.add(LineIndex::new(6), LineIndex::new(8));
+        folds.lines_replaced(3..4, 3);
+        assert_eq!(
+            folds
+                .iter()
+                .map(|f| (f.first.raw_value(), f.last.raw_value()))
+                .collect::<Vec<_>>(),
+            vec![(0, 11), (2, 6), (8, 10)]
+        );
+    }
+}
@@ -1,16 +1,22 @@
 /// Types related to syntax highlighting when draing contents of `Pager`s.
 ///
 /// `Highlighter` defines the main trait any highlighting engine must implement.
-/// `SyntectHighlighter` is the only included highlighter and should be sufficient for most
-/// use cases.
+/// `IncrementalHighlighter` is an alternative interface for engines that can resume highlighting
+/// from a saved state, which allows highlighting lines on demand.
+/// `SyntectHighlighter` (and its variant `OwnedSyntectHighlighter`) is the only included
+/// highlighter (implementing both traits) and should be sufficient for most use cases.
 use unsegen::base::{Color, LineIndex, StyleModifier, TextFormatModifier};
 
-use super::PagerLine;
+use super::{AnsiHighlighting, PagerLine};
 use syntect::highlighting;
-use syntect::parsing::{ParseState, ScopeStack, SyntaxDefinition};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
 
 use syntect::highlighting::Theme;
 
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
 /// Interface for anything that highlights the content of Pagers.
 ///
 /// `SyntectHighlighter` is an exemplary implementation that can should be sufficient for most
@@ -18,6 +24,43 @@ use syntect::highlighting::Theme;
 pub trait Highlighter {
     /// Compute highlighting information for the given range of lines.
     fn highlight<'a, L: Iterator<Item = &'a dyn PagerLine>>(&self, lines: L) -> HighlightInfo;
+
+    /// Style that is suggested for the active line of a `Pager`, if any.
+    fn active_line_style(&self) -> Option<StyleModifier> {
+        None
+    }
+}
+
+/// Interface for highlighters that process lines in order and can resume highlighting from a
+/// previously saved state.
+///
+/// Use `PagerContent::with_lazy_highlighter` to highlight lines only when they are about to be
+/// displayed. Snapshots of the state are kept periodically so that only a bounded number of lines
+/// have to be re-highlighted when jumping to an arbitrary position.
+pub trait IncrementalHighlighter {
+    /// State of the highlighter between two lines.
+    type State: Clone + PartialEq;
+
+    /// The state before the first line of the content.
+    fn initial_state(&self) -> Self::State;
+
+    /// Highlight the given consecutive lines, starting from (and updating) `state`.
+    ///
+    /// The result must contain the style changes (as in `HighlightInfo::style_changes`) for each
+    /// of the lines.
+    fn highlight_lines(
+        &self,
+        state: &mut Self::State,
+        lines: &[&str],
+    ) -> Vec<Vec<(usize, StyleModifier)>>;
+
+    /// Style that will be applied if no other style has been specified.
+    fn default_style(&self) -> StyleModifier;
+
+    /// Style that is suggested for the active line of a `Pager`, if any.
+    fn active_line_style(&self) -> Option<StyleModifier> {
+        None
+    }
 }
 
 /// Result of a highlighting operation (i.e., a call to Highlighter::highlight).
@@ -30,7 +73,7 @@ pub struct HighlightInfo {
 }
 
 const NO_CHANGE: Vec<(usize, StyleModifier)> = Vec::new();
-const NO_CHANGE_REF: &'static Vec<(usize, StyleModifier)> = &NO_CHANGE;
+const NO_CHANGE_REF: &Vec<(usize, StyleModifier)> = &NO_CHANGE;
 impl HighlightInfo {
     /// Empty highlighting result that will not apply any style changes.
     pub fn none() -> Self {
@@ -55,19 +98,29 @@ impl HighlightInfo {
 }
 
 /// A `Highlighter` using the `syntect` library as a backend.
+///
+/// As the highlighter borrows the syntax set and the theme, it can only be used with
+/// `PagerContent::with_lazy_highlighter` if both are `'static`. See `OwnedSyntectHighlighter` for
+/// a variant without this restriction.
 pub struct SyntectHighlighter<'a> {
     base_state: ParseState,
+    syntax_set: &'a SyntaxSet,
     theme: &'a Theme,
 }
 
 impl<'a> SyntectHighlighter<'a> {
-    /// Create a `SyntectHighlighter` using the specified `SyntaxDefinition` (e.g., what
-    /// programming language to assume) and the theme.
+    /// Create a `SyntectHighlighter` using the specified `SyntaxReference` (e.g., what
+    /// programming language to assume) from the `SyntaxSet` and the theme.
     ///
-    /// The theme reference has to be alive as long as the highlighter is active.
-    pub fn new(syntax: &SyntaxDefinition, theme: &'a highlighting::Theme) -> Self {
+    /// The syntax set and theme references have to be alive as long as the highlighter is active.
+    pub fn new(
+        syntax: &SyntaxReference,
+        syntax_set: &'a SyntaxSet,
+        theme: &'a highlighting::Theme,
+    ) -> Self {
         SyntectHighlighter {
             base_state: ParseState::new(syntax),
+            syntax_set,
             theme,
         }
     }
@@ -75,31 +128,426 @@ impl<'a> SyntectHighlighter<'a> {
 
 impl<'a> Highlighter for SyntectHighlighter<'a> {
     fn highlight<'b, L: Iterator<Item = &'b dyn PagerLine>>(&self, lines: L) -> HighlightInfo {
-        let mut info = HighlightInfo::none();
+        let lines = lines.map(|l| l.get_content()).collect::<Vec<_>>();
+        let mut state = self.initial_state();
+        HighlightInfo {
+            style_changes: self.highlight_lines(&mut state, &lines),
+            default_style: IncrementalHighlighter::default_style(self),
+        }
+    }
+
+    fn active_line_style(&self) -> Option<StyleModifier> {
+        IncrementalHighlighter::active_line_style(self)
+    }
+}
+
+impl<'a> IncrementalHighlighter for SyntectHighlighter<'a> {
+    type State = (ParseState, highlighting::HighlightState);
+
+    fn initial_state(&self) -> Self::State {
+        let highlighter = highlighting::Highlighter::new(self.theme);
+        let hstate = highlighting::HighlightState::new(&highlighter, ScopeStack::new());
+        (self.base_state.clone(), hstate)
+    }
+
+    fn highlight_lines(
+        &self,
+        state: &mut Self::State,
+        lines: &[&str],
+    ) -> Vec<Vec<(usize, StyleModifier)>> {
+        let highlighter = highlighting::Highlighter::new(self.theme);
+        let (ref mut parse_state, ref mut hstate) = *state;
+
+        lines
+            .iter()
+            .map(|line_content| {
+                let mut current_pos = 0;
+                let mut this_line_changes = Vec::new();
+
+                let ops = parse_state.parse_line(line_content, self.syntax_set);
+                for (style, fragment) in highlighting::HighlightIterator::new(
+                    hstate,
+                    &ops[..],
+                    line_content,
+                    &highlighter,
+                ) {
+                    this_line_changes.push((current_pos, to_unsegen_style_modifier(&style)));
+                    current_pos += fragment.len();
+                }
+                this_line_changes
+            })
+            .collect()
+    }
 
+    fn default_style(&self) -> StyleModifier {
         let highlighter = highlighting::Highlighter::new(self.theme);
-        let mut hstate = highlighting::HighlightState::new(&highlighter, ScopeStack::new());
-        let mut parse_state = self.base_state.clone();
-
-        for line in lines {
-            let line_content = line.get_content();
-            let mut current_pos = 0;
-            let mut this_line_changes = Vec::new();
-
-            let ops = parse_state.parse_line(line.get_content());
-            for (style, fragment) in highlighting::HighlightIterator::new(
-                &mut hstate,
-                &ops[..],
-                line_content,
-                &highlighter,
-            ) {
-                this_line_changes.push((current_pos, to_unsegen_style_modifier(&style)));
-                current_pos += fragment.len();
+        to_unsegen_style_modifier(&highlighter.get_default())
+    }
+
+    /// The background color of the active line is taken from the `line_highlight` setting of the
+    /// theme, if present.
+    fn active_line_style(&self) -> Option<StyleModifier> {
+        self.theme
+            .settings
+            .line_highlight
+            .map(|color| StyleModifier::new().bg_color(to_unsegen_color(color)))
+    }
+}
+
+/// A `SyntectHighlighter` that owns (shared references to) the syntax set and the theme, so that
+/// it can be stored alongside the content for lazy highlighting.
+pub struct OwnedSyntectHighlighter {
+    base_state: ParseState,
+    syntax_set: Arc<SyntaxSet>,
+    theme: Arc<Theme>,
+}
+
+impl OwnedSyntectHighlighter {
+    /// Create an `OwnedSyntectHighlighter` using the specified `SyntaxReference` (e.g., what
+    /// programming language to assume) from the `SyntaxSet` and the theme.
+    pub fn new(syntax: &SyntaxReference, syntax_set: Arc<SyntaxSet>, theme: Arc<Theme>) -> Self {
+        OwnedSyntectHighlighter {
+            base_state: ParseState::new(syntax),
+            syntax_set,
+            theme,
+        }
+    }
+
+    fn borrowed(&self) -> SyntectHighlighter<'_> {
+        SyntectHighlighter {
+            base_state: self.base_state.clone(),
+            syntax_set: &self.syntax_set,
+            theme: &self.theme,
+        }
+    }
+}
+
+impl Highlighter for OwnedSyntectHighlighter {
+    fn highlight<'b, L: Iterator<Item = &'b dyn PagerLine>>(&self, lines: L) -> HighlightInfo {
+        self.borrowed().highlight(lines)
+    }
+
+    fn active_line_style(&self) -> Option<StyleModifier> {
+        IncrementalHighlighter::active_line_style(self)
+    }
+}
+
+impl IncrementalHighlighter for OwnedSyntectHighlighter {
+    type State = (ParseState, highlighting::HighlightState);
+
+    fn initial_state(&self) -> Self::State {
+        self.borrowed().initial_state()
+    }
+
+    fn highlight_lines(
+        &self,
+        state: &mut Self::State,
+        lines: &[&str],
+    ) -> Vec<Vec<(usize, StyleModifier)>> {
+        self.borrowed().highlight_lines(state, lines)
+    }
+
+    fn default_style(&self) -> StyleModifier {
+        IncrementalHighlighter::default_style(&self.borrowed())
+    }
+
+    fn active_line_style(&self) -> Option<StyleModifier> {
+        IncrementalHighlighter::active_line_style(&self.borrowed())
+    }
+}
+
+/// Number of lines between two saved states of a lazily highlighted content.
+const CHECKPOINT_INTERVAL: usize = 128;
+
+/// Maximum number of blocks (of `CHECKPOINT_INTERVAL` lines) of highlighting results that are kept
+/// in memory for lazily highlighted content.
+const MAX_CACHED_BLOCKS: usize = 64;
+
+/// Highlighting information as stored in `PagerContent`.
+pub(crate) enum ContentHighlighting {
+    /// No highlighting at all.
+    None,
+    /// Highlighting information computed for all lines up front using a `Highlighter` that
+    /// cannot be updated when lines are added or replaced, along with the suggested style of the
+    /// active line.
+    Fixed(HighlightInfo, Option<StyleModifier>),
+    /// Highlighting information parsed from ANSI escape sequences, which is extended when lines
+    /// with escape sequences are appended (see `PagerContent::append_ansi_lines`).
+    Ansi(AnsiHighlighting),
+    /// Highlighting information computed (on demand or up front) using an
+    /// `IncrementalHighlighter`, which is updated when lines are added or replaced.
+    Incremental(Box<dyn IncrementalHighlighting>),
+}
+
+impl ContentHighlighting {
+    /// Create lazy highlighting information using the given highlighter.
+    pub fn lazy<H: IncrementalHighlighter + 'static>(highlighter: H) -> Self {
+        ContentHighlighting::Incremental(Box::new(CheckpointedHighlighting::new(
+            highlighter,
+            false,
+        )))
+    }
+
+    /// Highlight all `num_lines` lines (see `prepare`) using the given highlighter and keep the
+    /// results.
+    pub fn eager<'a, H: IncrementalHighlighter + 'static>(
+        highlighter: H,
+        num_lines: usize,
+        lines: &dyn Fn(usize) -> Option<&'a str>,
+    ) -> Self {
+        let mut highlighting = CheckpointedHighlighting::new(highlighter, true);
+        highlighting.prepare(0..num_lines, lines);
+        ContentHighlighting::Incremental(Box::new(highlighting))
+    }
+
+    /// Make sure that the highlighting information for all lines in `range` is available.
+    ///
+    /// `lines` is used to access the content of the lines and has to return `None` for indices
+    /// past the end of the content.
+    pub fn prepare<'a>(&mut self, range: Range<usize>, lines: &dyn Fn(usize) -> Option<&'a str>) {
+        if let ContentHighlighting::Incremental(ref mut incremental) = *self {
+            incremental.prepare(range, lines);
+        }
+    }
+
+    /// Check whether the highlighting information can be updated after replacing the lines in
+    /// `range` of content with `num_lines` lines (see `lines_replaced`).
+    ///
+    /// Fixed information (and information parsed from ANSI escape sequences) only stays valid if
+    /// lines are removed from the end of the content, as the highlighting of a line may depend on
+    /// all preceding lines.
+    pub fn can_replace(&self, range: Range<usize>, num_lines: usize, adds_lines: bool) -> bool {
+        match *self {
+            ContentHighlighting::Fixed(..) | ContentHighlighting::Ansi(_) => {
+                !adds_lines && range.end >= num_lines
+            }
+            ContentHighlighting::None | ContentHighlighting::Incremental(_) => true,
+        }
+    }
+
+    /// Update the highlighting information after the lines in `range` have been replaced by
+    /// `num_new_lines` new lines.
+    ///
+    /// Incrementally computed information is recomputed on demand, starting at the first changed
+    /// line and reusing the previous results once the highlighter state converges. Fixed
+    /// information must only be updated if `can_replace` allows it.
+    pub fn lines_replaced(&mut self, range: Range<usize>, num_new_lines: usize) {
+        match *self {
+            ContentHighlighting::None => {}
+            ContentHighlighting::Fixed(ref mut info, _) => {
+                debug_assert_eq!(num_new_lines, 0, "fixed highlighting cannot be updated");
+                info.style_changes.truncate(range.start);
+            }
+            ContentHighlighting::Ansi(ref mut ansi) => {
+                debug_assert_eq!(
+                    num_new_lines, 0,
+                    "ANSI lines have to be parsed when appended"
+                );
+                ansi.truncate(range.start);
+            }
+            ContentHighlighting::Incremental(ref mut incremental) => {
+                incremental.lines_replaced(range, num_new_lines)
+            }
+        }
+    }
+
+    /// Get any style changes for the specified line.
+    ///
+    /// For incremental highlighting, the line has to be `prepare`d beforehand.
+    pub fn get_info_for_line<I: Into<LineIndex>>(&self, line: I) -> &[(usize, StyleModifier)] {
+        match *self {
+            ContentHighlighting::None => &[],
+            ContentHighlighting::Fixed(ref info, _) => &info.get_info_for_line(line)[..],
+            ContentHighlighting::Ansi(ref ansi) => &ansi.info().get_info_for_line(line)[..],
+            ContentHighlighting::Incremental(ref incremental) => {
+                incremental.get_info_for_line(line.into().raw_value())
+            }
+        }
+    }
+
+    /// Return the default style, i.e., the style that will be applied to text if no modifications
+    /// are present.
+    pub fn default_style(&self) -> StyleModifier {
+        match *self {
+            ContentHighlighting::None => StyleModifier::new(),
+            ContentHighlighting::Fixed(ref info, _) => info.default_style(),
+            ContentHighlighting::Ansi(ref ansi) => ansi.info().default_style(),
+            ContentHighlighting::Incremental(ref incremental) => incremental.default_style(),
+        }
+    }
+
+    /// Return the style that is suggested for the active line, if any.
+    pub fn active_line_style(&self) -> Option<StyleModifier> {
+        match *self {
+            ContentHighlighting::None | ContentHighlighting::Ansi(_) => None,
+            ContentHighlighting::Fixed(_, style) => style,
+            ContentHighlighting::Incremental(ref incremental) => incremental.active_line_style(),
+        }
+    }
+}
+
+/// Type-erased interface of an `IncrementalHighlighter` and its cached results.
+pub(crate) trait IncrementalHighlighting {
+    fn prepare<'a>(&mut self, range: Range<usize>, lines: &dyn Fn(usize) -> Option<&'a str>);
+    fn lines_replaced(&mut self, range: Range<usize>, num_new_lines: usize);
+    fn get_info_for_line(&self, line: usize) -> &[(usize, StyleModifier)];
+    fn default_style(&self) -> StyleModifier;
+    fn active_line_style(&self) -> Option<StyleModifier>;
+}
+
+struct CheckpointedHighlighting<H: IncrementalHighlighter> {
+    highlighter: H,
+    /// `checkpoints[i]` is the highlighter state before line `i * CHECKPOINT_INTERVAL`.
+    checkpoints: Vec<H::State>,
+    /// Checkpoints from before the last modification of the content, which become valid again
+    /// once the highlighter state converges. `candidates[i]` is the candidate for checkpoint
+    /// `candidates_start + i`.
+    candidates: Vec<H::State>,
+    candidates_start: usize,
+    /// Highlighting results for (recently accessed) blocks of `CHECKPOINT_INTERVAL` lines.
+    blocks: HashMap<usize, Vec<Vec<(usize, StyleModifier)>>>,
+    /// Keep the results of all blocks instead of at most `MAX_CACHED_BLOCKS`.
+    keep_all_blocks: bool,
+}
+
+impl<H: IncrementalHighlighter> CheckpointedHighlighting<H> {
+    fn new(highlighter: H, keep_all_blocks: bool) -> Self {
+        let initial_state = highlighter.initial_state();
+        CheckpointedHighlighting {
+            highlighter,
+            checkpoints: vec![initial_state],
+            candidates: Vec::new(),
+            candidates_start: 0,
+            blocks: HashMap::new(),
+            keep_all_blocks,
+        }
+    }
+
+    /// Highlight the lines of the specified block, starting from the checkpoint at its beginning,
+    /// which therefore has to be present.
+    ///
+    /// If the block is complete, the checkpoint for the following block is stored. Results are
+    /// only kept if `keep_results` is set. Returns whether the block was complete.
+    fn highlight_block<'a>(
+        &mut self,
+        block: usize,
+        lines: &dyn Fn(usize) -> Option<&'a str>,
+        keep_results: bool,
+    ) -> bool {
+        let start = block * CHECKPOINT_INTERVAL;
+        let block_lines = (start..start + CHECKPOINT_INTERVAL)
+            .map(lines)
+            .take_while(|line| line.is_some())
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        let mut state = self.checkpoints[block].clone();
+        let results = self.highlighter.highlight_lines(&mut state, &block_lines);
+
+        let complete = block_lines.len() == CHECKPOINT_INTERVAL;
+        if complete && self.checkpoints.len() == block + 1 {
+            let next = block + 1;
+            let converged = next >= self.candidates_start
+                && self
+                    .candidates
+                    .get(next - self.candidates_start)
+                    .map(|candidate| *candidate == state)
+                    .unwrap_or(false);
+            self.checkpoints.push(state);
+            if converged {
+                let first_valid = next - self.candidates_start + 1;
+                self.checkpoints
+                    .extend(self.candidates.drain(first_valid..));
+                self.candidates.clear();
+            }
+        }
+        if keep_results {
+            self.blocks.insert(block, results);
+        }
+        complete
+    }
+}
+
+impl<H: IncrementalHighlighter> IncrementalHighlighting for CheckpointedHighlighting<H> {
+    fn prepare<'a>(&mut self, range: Range<usize>, lines: &dyn Fn(usize) -> Option<&'a str>) {
+        if range.start >= range.end {
+            return;
+        }
+        let first_block = range.start / CHECKPOINT_INTERVAL;
+        let last_block = (range.end - 1) / CHECKPOINT_INTERVAL;
+        for block in first_block..=last_block {
+            if self.blocks.contains_key(&block) {
+                continue;
+            }
+            // Walk forward from the last known state to the beginning of the block.
+            while self.checkpoints.len() <= block {
+                let last_checkpoint = self.checkpoints.len() - 1;
+                if !self.highlight_block(last_checkpoint, lines, false) {
+                    // The content ends before the requested block.
+                    return;
+                }
+            }
+            if !self.highlight_block(block, lines, true) {
+                // The content ends within this block.
+                break;
             }
-            info.style_changes.push(this_line_changes);
         }
-        info.default_style = to_unsegen_style_modifier(&highlighter.get_default());
-        info
+
+        // Drop the results that are farthest away from the requested range.
+        while !self.keep_all_blocks && self.blocks.len() > MAX_CACHED_BLOCKS {
+            let distance = |block: usize| {
+                if block < first_block {
+                    first_block - block
+                } else {
+                    block.saturating_sub(last_block)
+                }
+            };
+            let farthest = *self
+                .blocks
+                .keys()
+                .max_by_key(|&&block| distance(block))
+                .expect("blocks not empty");
+            self.blocks.remove(&farthest);
+        }
+    }
+
+    fn lines_replaced(&mut self, range: Range<usize>, num_new_lines: usize) {
+        let first_invalid_block = range.start / CHECKPOINT_INTERVAL;
+
+        // Checkpoints after the replaced range are still valid if the highlighter state converges
+        // and they are shifted by whole blocks.
+        let delta = num_new_lines as isize - (range.end - range.start) as isize;
+        let first_candidate = ::std::cmp::max(
+            range.end.div_ceil(CHECKPOINT_INTERVAL),
+            first_invalid_block + 1,
+        );
+        if delta % CHECKPOINT_INTERVAL as isize == 0 && first_candidate < self.checkpoints.len() {
+            self.candidates_start =
+                (first_candidate as isize + delta / CHECKPOINT_INTERVAL as isize) as usize;
+            self.candidates = self.checkpoints.split_off(first_candidate);
+        } else {
+            self.candidates.clear();
+        }
+
+        // The checkpoint at the start of the first changed block is still valid, as all preceding
+        // lines are unchanged.
+        self.checkpoints.truncate(first_invalid_block + 1);
+        self.blocks.retain(|&block, _| block < first_invalid_block);
+    }
+
+    fn get_info_for_line(&self, line: usize) -> &[(usize, StyleModifier)] {
+        self.blocks
+            .get(&(line / CHECKPOINT_INTERVAL))
+            .and_then(|block| block.get(line % CHECKPOINT_INTERVAL))
+            .map(|changes| &changes[..])
+            .unwrap_or(&[])
+    }
+
+    fn default_style(&self) -> StyleModifier {
+        self.highlighter.default_style()
+    }
+
+    fn active_line_style(&self) -> Option<StyleModifier> {
+        self.highlighter.active_line_style()
     }
 }
 
@@ -122,3 +570,252 @@ fn to_unsegen_style_modifier(style: &highlighting::Style) -> StyleModifier {
         .bg_color(to_unsegen_color(style.background))
         .format(to_unsegen_text_format(style.font_style))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Highlights `/* ... */` comments, which may span multiple lines, and counts the lines it
+    /// has highlighted.
+    #[derive(Default)]
+    struct CommentHighlighter {
+        num_highlighted_lines: Rc<Cell<usize>>,
+    }
+
+    fn comment_style() -> StyleModifier {
+        StyleModifier::new().bold(true)
+    }
+
+    impl IncrementalHighlighter for CommentHighlighter {
+        /// Whether the highlighter is inside of a comment.
+        type State = bool;
+
+        fn initial_state(&self) -> bool {
+            false
+        }
+
+        fn highlight_lines(
+            &self,
+            in_comment: &mut bool,
+            lines: &[&str],
+        ) -> Vec<Vec<(usize, StyleModifier)>> {
+            self.num_highlighted_lines
+                .set(self.num_highlighted_lines.get() + lines.len());
+            lines
+                .iter()
+                .map(|line| {
+                    let mut changes = Vec::new();
+                    if *in_comment {
+                        changes.push((0, comment_style()));
+                    }
+                    let mut pos = 0;
+                    loop {
+                        let (delimiter, style) = if *in_comment {
+                            ("*/", StyleModifier::new())
+                        } else {
+                            ("/*", comment_style())
+                        };
+                        match line[pos..].find(delimiter) {
+                            Some(offset) => {
+                                pos += offset + delimiter.len();
+                                let start = if *in_comment { pos } else { pos - 2 };
+                                changes.push((start, style));
+                                *in_comment = !*in_comment;
+                            }
+                            None => break,
+                        }
+                    }
+                    changes
+                })
+                .collect()
+        }
+
+        fn default_style(&self) -> StyleModifier {
+            StyleModifier::new()
+        }
+    }
+
+    fn code(num_lines: usize) -> Vec<String> {
+        (0..num_lines)
+            .map(|i| match i % 50 {
+                10 => "a /* b".to_owned(),
+                13 => "c */ d /* e */".to_owned(),
+                _ => format!("line {}", i),
+            })
+            .collect()
+    }
+
+    /// Highlight all lines at once, starting from the initial state.
+    fn full_highlight(lines: &[String]) -> Vec<Vec<(usize, StyleModifier)>> {
+        let highlighter = CommentHighlighter::default();
+        let lines = lines.iter().map(String::as_str).collect::<Vec<_>>();
+        highlighter.highlight_lines(&mut highlighter.initial_state(), &lines)
+    }
+
+    fn prepare_all(
+        highlighting: &mut CheckpointedHighlighting<CommentHighlighter>,
+        lines: &[String],
+    ) {
+        highlighting.prepare(0..lines.len(), &|i| lines.get(i).map(String::as_str));
+    }
+
+    fn assert_matches_full_highlight(
+        highlighting: &CheckpointedHighlighting<CommentHighlighter>,
+        lines: &[String],
+    ) {
+        for (i, changes) in full_highlight(lines).iter().enumerate() {
+            assert_eq!(&highlighting.get_info_for_line(i), changes, "line {}", i);
+        }
+    }
+
+    fn num_highlighted_lines(highlighting: &CheckpointedHighlighting<CommentHighlighter>) -> usize {
+        highlighting.highlighter.num_highlighted_lines.get()
+    }
+
+    /// Replace the lines in `range` in both `lines` and `highlighting`.
+    fn replace(
+        highlighting: &mut CheckpointedHighlighting<CommentHighlighter>,
+        lines: &mut Vec<String>,
+        range: Range<usize>,
+        new_lines: &[&str],
+    ) {
+        lines
+            .splice(range.clone(), new_lines.iter().map(|l| l.to_string()))
+            .for_each(drop);
+        highlighting.lines_replaced(range, new_lines.len());
+    }
+
+    #[test]
+    fn prepare_from_checkpoints() {
+        let lines = code(1000);
+        let full = full_highlight(&lines);
+        let mut highlighting = CheckpointedHighlighting::new(CommentHighlighter::default(), false);
+
+        // Blocks before the requested one are only highlighted to obtain their checkpoints.
+        highlighting.prepare(700..710, &|i| lines.get(i).map(String::as_str));
+        assert_eq!(highlighting.checkpoints.len(), 7);
+        assert_eq!(
+            num_highlighted_lines(&highlighting),
+            6 * CHECKPOINT_INTERVAL
+        );
+        assert_eq!(highlighting.get_info_for_line(0), &[]);
+        for (i, changes) in full.iter().enumerate().skip(640).take(CHECKPOINT_INTERVAL) {
+            assert_eq!(&highlighting.get_info_for_line(i), changes, "line {}", i);
+        }
+
+        // Earlier blocks are highlighted starting from their checkpoint.
+        highlighting.prepare(10..20, &|i| lines.get(i).map(String::as_str));
+        assert_eq!(
+            num_highlighted_lines(&highlighting),
+            7 * CHECKPOINT_INTERVAL
+        );
+        for (i, changes) in full.iter().enumerate().take(CHECKPOINT_INTERVAL) {
+            assert_eq!(&highlighting.get_info_for_line(i), changes, "line {}", i);
+        }
+    }
+
+    #[test]
+    fn prepare_past_the_end() {
+        let lines = code(300);
+        let mut highlighting = CheckpointedHighlighting::new(CommentHighlighter::default(), true);
+        highlighting.prepare(0..usize::MAX, &|i| lines.get(i).map(String::as_str));
+        assert_eq!(num_highlighted_lines(&highlighting), 300);
+        assert_matches_full_highlight(&highlighting, &lines);
+    }
+
+    #[test]
+    fn eager_highlights_each_line_once() {
+        let lines = code(300);
+        let highlighter = CommentHighlighter::default();
+        let num_highlighted_lines = highlighter.num_highlighted_lines.clone();
+        let highlighting = ContentHighlighting::eager(highlighter, lines.len(), &|i| {
+            lines.get(i).map(String::as_str)
+        });
+        assert_eq!(num_highlighted_lines.get(), 300);
+        for (i, changes) in full_highlight(&lines).iter().enumerate() {
+            assert_eq!(
+                &highlighting.get_info_for_line(LineIndex::new(i)),
+                changes,
+                "line {}",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn replace_without_state_change_converges() {
+        let mut lines = code(1000);
+        let mut highlighting = CheckpointedHighlighting::new(CommentHighlighter::default(), true);
+        prepare_all(&mut highlighting, &lines);
+        let num_checkpoints = highlighting.checkpoints.len();
+
+        replace(&mut highlighting, &mut lines, 200..201, &["changed"]);
+        assert_eq!(highlighting.checkpoints.len(), 2);
+
+        // Only the changed block is highlighted again, as the state after it has not changed.
+        let before = num_highlighted_lines(&highlighting);
+        highlighting.prepare(200..201, &|i| lines.get(i).map(String::as_str));
+        assert_eq!(highlighting.checkpoints.len(), num_checkpoints);
+        assert_eq!(
+            num_highlighted_lines(&highlighting) - before,
+            CHECKPOINT_INTERVAL
+        );
+
+        prepare_all(&mut highlighting, &lines);
+        assert_matches_full_highlight(&highlighting, &lines);
+    }
+
+    #[test]
+    fn replace_with_state_change() {
+        let mut lines = code(1000);
+        let mut highlighting = CheckpointedHighlighting::new(CommentHighlighter::default(), true);
+        prepare_all(&mut highlighting, &lines);
+
+        // Open a comment that is closed by the "*/" in line 263.
+        replace(&mut highlighting, &mut lines, 250..251, &["/*"]);
+        prepare_all(&mut highlighting, &lines);
+        assert_matches_full_highlight(&highlighting, &lines);
+        assert!(highlighting.checkpoints[2]);
+
+        // Remove the end of the comment, so that it extends to the next "*/" in line 313.
+        replace(&mut highlighting, &mut lines, 263..264, &["c"]);
+        prepare_all(&mut highlighting, &lines);
+        assert_matches_full_highlight(&highlighting, &lines);
+        assert_eq!(highlighting.get_info_for_line(300), &[(0, comment_style())]);
+    }
+
+    #[test]
+    fn insert_and_remove_whole_blocks() {
+        let mut lines = code(1000);
+        let mut highlighting = CheckpointedHighlighting::new(CommentHighlighter::default(), true);
+        prepare_all(&mut highlighting, &lines);
+
+        let new_lines = vec!["new"; 2 * CHECKPOINT_INTERVAL];
+        replace(&mut highlighting, &mut lines, 300..300, &new_lines);
+        assert_eq!(highlighting.candidates_start, 5);
+        prepare_all(&mut highlighting, &lines);
+        assert_matches_full_highlight(&highlighting, &lines);
+
+        replace(&mut highlighting, &mut lines, 0..CHECKPOINT_INTERVAL, &[]);
+        prepare_all(&mut highlighting, &lines);
+        assert_matches_full_highlight(&highlighting, &lines);
+    }
+
+    #[test]
+    fn insert_and_remove_partial_blocks() {
+        let mut lines = code(1000);
+        let mut highlighting = CheckpointedHighlighting::new(CommentHighlighter::default(), true);
+        prepare_all(&mut highlighting, &lines);
+
+        replace(&mut highlighting, &mut lines, 140..140, &["a", "/* b", "c"]);
+        assert!(highlighting.candidates.is_empty());
+        prepare_all(&mut highlighting, &lines);
+        assert_matches_full_highlight(&highlighting, &lines);
+
+        replace(&mut highlighting, &mut lines, 900..1003, &[]);
+        prepare_all(&mut highlighting, &lines);
+        assert_matches_full_highlight(&highlighting, &lines);
+    }
+}
@@ -0,0 +1,93 @@
+/// Information about where the parts of a `Pager` were drawn, e.g., to map positions in the window
+/// (of mouse clicks) back to lines or to display the visible range of lines in a status line.
+use unsegen::base::basic_types::*;
+
+use std::ops::Range;
+
+/// The layout of the last draw of a `Pager`, see `Pager::layout`.
+///
+/// The window of the pager is split into (from left to right) the decoration of the lines, the
+/// fold column and the lines themselves. The decoration and the fold column together form the
+/// gutter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PagerLayout {
+    /// All lines that were drawn (at least partially) to the window along with the rows they
+    /// occupy. The rows of partially visible lines extend beyond the window.
+    pub(crate) lines: Vec<(LineIndex, Range<RowIndex>)>,
+    pub(crate) decoration_width: usize,
+    pub(crate) fold_column_width: usize,
+    pub(crate) content_width: usize,
+    pub(crate) height: usize,
+}
+
+impl PagerLayout {
+    /// Get all lines that are (at least partially) visible, in order from top to bottom, along
+    /// with the rows they occupy.
+    ///
+    /// The rows of lines that are only partially visible extend beyond the window, i.e., they may
+    /// start at a negative row or end after the last row of the window.
+    pub fn lines(&self) -> &[(LineIndex, Range<RowIndex>)] {
+        &self.lines
+    }
+
+    /// Get the topmost (at least partially) visible line, if any.
+    pub fn first_visible_line(&self) -> Option<LineIndex> {
+        self.lines.first().map(|&(line, _)| line)
+    }
+
+    /// Get the bottommost (at least partially) visible line, if any.
+    pub fn last_visible_line(&self) -> Option<LineIndex> {
+        self.lines.last().map(|&(line, _)| line)
+    }
+
+    /// Get the rows occupied by the given line, if it is visible.
+    pub fn rows_of_line<I: Into<LineIndex>>(&self, line: I) -> Option<Range<RowIndex>> {
+        let line = line.into();
+        self.lines
+            .iter()
+            .find(|entry| entry.0 == line)
+            .map(|entry| entry.1.clone())
+    }
+
+    /// Find the line that was drawn to the given row along with all rows it occupies.
+    pub fn line_at_row(&self, row: RowIndex) -> Option<(LineIndex, Range<RowIndex>)> {
+        if row < RowIndex::new(0) || row >= RowIndex::new(self.height as i32) {
+            return None;
+        }
+        self.lines
+            .iter()
+            .find(|line| line.1.start <= row && row < line.1.end)
+            .cloned()
+    }
+
+    /// Get the width of the decoration to the left of the lines.
+    pub fn decoration_width(&self) -> usize {
+        self.decoration_width
+    }
+
+    /// Get the width of the fold column between the decoration and the lines (0 if it was not
+    /// drawn).
+    pub fn fold_column_width(&self) -> usize {
+        self.fold_column_width
+    }
+
+    /// Get the total width of the gutter, i.e., the decoration and the fold column.
+    pub fn gutter_width(&self) -> usize {
+        self.decoration_width + self.fold_column_width
+    }
+
+    /// Get the width of the part of the window that displays the lines.
+    pub fn content_width(&self) -> usize {
+        self.content_width
+    }
+
+    /// Get the total width of the window.
+    pub fn width(&self) -> usize {
+        self.gutter_width() + self.content_width
+    }
+
+    /// Get the height of the window, i.e., the number of visible rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
@@ -6,10 +6,15 @@
 //!
 //! use std::io::{stdin, stdout};
 //! use unsegen::base::Terminal;
-//! use unsegen::input::{Input, Key, ScrollBehavior};
+//! use unsegen::input::Input;
 //! use unsegen::widget::{RenderingHints, Widget};
 //!
-//! use unsegen_pager::{Pager, PagerContent, SyntaxSet, SyntectHighlighter, ThemeSet};
+//! use std::sync::Arc;
+//!
+//! use unsegen_pager::{
+//!     OwnedSyntectHighlighter, Pager, PagerBehavior, PagerContent, PagerKeyBindings,
+//!     PagerKeyState, SyntaxSet, ThemeSet,
+//! };
 //!
 //! fn main() {
 //!     let stdout = stdout();
@@ -18,36 +23,31 @@
 //!
 //!     let file = "path/to/some/file";
 //!
-//!     let syntax_set = SyntaxSet::load_defaults_nonewlines();
+//!     let syntax_set = Arc::new(SyntaxSet::load_defaults_nonewlines());
 //!     let syntax = syntax_set
 //!         .find_syntax_for_file(&file)
 //!         .unwrap()
 //!         .unwrap_or(syntax_set.find_syntax_plain_text());
 //!
-//!     let theme_set = ThemeSet::load_defaults();
-//!     let theme = &theme_set.themes["base16-ocean.dark"];
+//!     let mut theme_set = ThemeSet::load_defaults();
+//!     let theme = Arc::new(theme_set.themes.remove("base16-ocean.dark").unwrap());
 //!
-//!     let highlighter = SyntectHighlighter::new(syntax, theme);
+//!     let highlighter = OwnedSyntectHighlighter::new(syntax, syntax_set.clone(), theme);
 //!     let mut pager = Pager::new();
 //!     pager.load(
 //!         PagerContent::from_file(&file)
 //!             .unwrap()
-//!             .with_highlighter(&highlighter),
+//!             .with_lazy_highlighter(highlighter),
 //!     );
 //!
 //!     let mut term = Terminal::new(stdout.lock()).unwrap();
 //!
+//!     let bindings = PagerKeyBindings::less();
+//!     let mut key_state = PagerKeyState::new();
+//!
 //!     for input in Input::read_all(stdin) {
 //!         let input = input.unwrap();
-//!         input.chain(
-//!             ScrollBehavior::new(&mut pager)
-//!                 .forwards_on(Key::Down)
-//!                 .forwards_on(Key::Char('j'))
-//!                 .backwards_on(Key::Up)
-//!                 .backwards_on(Key::Char('k'))
-//!                 .to_beginning_on(Key::Home)
-//!                 .to_end_on(Key::End),
-//!         );
+//!         input.chain(PagerBehavior::new(&mut pager, &bindings, &mut key_state));
 //!         // Put more application logic here...
 //!
 //!         {
@@ -59,26 +59,57 @@
 //! }
 //! ```
 
+extern crate memmap2;
+extern crate regex;
 extern crate syntect;
+extern crate unicode_segmentation;
+extern crate unicode_width;
 extern crate unsegen;
 
+mod annotation;
+mod ansi;
+mod behavior;
 mod decorating;
+mod filter;
+mod folding;
 mod highlighting;
+mod layout;
+mod marks;
+mod scrollbar;
+mod source;
+mod status;
+mod text;
 
+pub use annotation::*;
+pub use ansi::*;
+pub use behavior::*;
 pub use decorating::*;
+pub use folding::*;
 pub use highlighting::*;
+pub use layout::*;
+pub use scrollbar::*;
+pub use source::*;
+pub use status::*;
 
 pub use syntect::highlighting::{Theme, ThemeSet};
-pub use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+pub use syntect::parsing::{SyntaxDefinition, SyntaxReference, SyntaxSet};
+
+use filter::{LineFilter, Visibility};
+use marks::{update_marks, JumpList};
+use regex::Regex;
 
 use unsegen::base::{
-    basic_types::*, BoolModifyMode, Cursor, GraphemeCluster, StyleModifier, Window, WrappingMode,
+    basic_types::*, BoolModifyMode, Color, Cursor, GraphemeCluster, StyleModifier, Window,
+    WrappingMode,
 };
-use unsegen::input::{OperationResult, Scrollable};
+use unsegen::input::{MouseButton, MouseEvent, OperationResult, Scrollable};
 use unsegen::widget::{layout_linearly, Demand, Demand2D, RenderingHints, Widget};
 
+use std::borrow::Cow;
+use std::cell::{Cell, Ref, RefCell};
 use std::cmp::{max, min};
-use std::ops::{Bound, RangeBounds};
+use std::collections::BTreeMap;
+use std::ops::{Bound, Range, RangeBounds};
 
 /// Main `Widget`, may (or may not) store content, but defines static types for content and
 /// decoration.
@@ -87,55 +118,397 @@ use std::ops::{Bound, RangeBounds};
 ///
 /// In addition to the `PagerContent`, it has a concept of an 'active line' that can be updated via
 /// user interaction (using the `Scrollable` implementation) and is always displayed when drawn to
-/// a window.
+/// a window. Where in the window the active line is displayed can be configured using
+/// `set_active_line_position`.
+///
+/// Alternatively, the pager can be used for read-only viewing without an active line (see
+/// `set_scroll_mode`), in which case the same operations move the top line of the viewport. In
+/// both modes, `scroll_page_forwards` and friends move by the height of the window (as of the last
+/// `draw`).
+///
+/// For content that grows over time (e.g., log files), follow mode (see `set_follow_mode`) keeps
+/// the last line active while new lines are added using `append_lines`.
+///
+/// Long lines are wrapped by default, but can also be cut off at the window border (see
+/// `set_line_wrapping`), in which case the visible part can be moved using `scroll_left` and
+/// `scroll_right`.
+///
+/// Ranges of lines can be collapsed using folds (see `PagerContent::folds_mut`), which are opened
+/// and closed using `toggle_fold` and friends. Lines hidden by closed folds are skipped when
+/// scrolling.
+///
+/// Using `set_filter` (or `set_filter_pattern`), only the lines matching a predicate are
+/// displayed. Line indices (e.g., of the active line) still refer to the lines of the content.
+///
+/// A search pattern can be set using `set_search_pattern`. All matches of the pattern are
+/// highlighted when drawing and can be navigated using `search_next` and `search_previous`.
+///
+/// Lines can be remembered using named marks (see `set_mark` and `go_to_mark`). Additionally, the
+/// active line before large jumps (`go_to_line`, searching, `scroll_to_beginning`,
+/// `scroll_to_end` and `go_to_mark`) is recorded in a jump list, which can be navigated using
+/// `jump_back` and `jump_forward`.
+///
+/// A range of lines can be selected using `start_selection`, which sets the anchor of the
+/// selection to the active line. The selection then extends from the anchor to the active line
+/// and is highlighted using the selection style. Use `selected_lines` or `selected_text` to
+/// retrieve the selected lines.
+///
+/// Optionally, a column cursor within the active line can be enabled using `set_column_cursor`.
+/// It is moved using `cursor_left`, `cursor_right` and friends and keeps its display column when
+/// the active line changes (as far as the line is wide enough). `word_under_cursor` and
+/// `token_under_cursor` provide the text at the cursor, e.g., to follow a URL.
+///
+/// Mouse events can be passed to `handle_mouse_event`: Clicking a line makes it active, clicks on
+/// the decoration are reported to the `LineDecorator` and the mouse wheel scrolls the content.
+/// The positions of lines in the window as of the last draw are available via `layout`.
 pub struct Pager<L, D = NoDecorator<L>>
 where
-    L: PagerLine,
+    L: PagerLine + ?Sized,
     D: LineDecorator,
 {
     content: Option<PagerContent<L, D>>,
     current_line: LineIndex,
+    scroll_mode: ScrollMode,
+    active_line_position: ActiveLinePosition,
+    viewport_top: Cell<LineIndex>,
+    follow_mode: bool,
+    following: bool,
+    line_wrapping: LineWrapping,
+    horizontal_offset: usize,
+    show_cut_off_indicators: bool,
+    search: Option<Regex>,
+    search_match_style: StyleModifier,
+    active_line_style: Option<StyleModifier>,
+    show_fold_column: bool,
+    fold_summary_style: StyleModifier,
+    filter: Option<LineFilter<L>>,
+    marks: BTreeMap<char, usize>,
+    jump_list: JumpList,
+    selection_anchor: Option<LineIndex>,
+    selection_style: StyleModifier,
+    cursor_column: Option<usize>,
+    cursor_style: StyleModifier,
+    last_layout: RefCell<PagerLayout>,
+    mouse_wheel_lines: usize,
+}
+
+/// Specifies what is moved when scrolling a `Pager`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollMode {
+    /// Scrolling moves the (highlighted) active line. The view follows the active line, which is
+    /// kept near the center of the window.
+    ActiveLine,
+    /// Scrolling moves the view, the "active line" is the top line of the window and is not
+    /// highlighted.
+    Viewport,
+}
+
+/// Specifies where the active line of a `Pager` (in `ScrollMode::ActiveLine`) is displayed
+/// within the window.
+///
+/// Regardless of the policy, the window is never scrolled beyond the beginning of the content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActiveLinePosition {
+    /// Keep the active line at the vertical center of the window, unless the end of the content is
+    /// visible.
+    Centered,
+    /// Only scroll the view if fewer than the specified number of lines would be visible above or
+    /// below the active line (like vim's `scrolloff`).
+    ScrollOff(usize),
+    /// Keep the active line at the top of the window.
+    PinnedTop,
+    /// Keep the active line at the bottom of the window.
+    PinnedBottom,
+}
+
+/// Specifies how lines that are wider than the window of a `Pager` are displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineWrapping {
+    /// Continue lines on the following rows.
+    Wrap,
+    /// Cut off lines at the window border. The visible part of all lines can be moved using
+    /// horizontal scrolling.
+    Truncate,
 }
 
 impl<L, D> Default for Pager<L, D>
 where
-    L: PagerLine,
+    L: PagerLine + ?Sized,
     D: LineDecorator<Line = L>,
 {
     fn default() -> Self {
         Pager {
             content: None,
             current_line: LineIndex::new(0),
+            scroll_mode: ScrollMode::ActiveLine,
+            active_line_position: ActiveLinePosition::Centered,
+            viewport_top: Cell::new(LineIndex::new(0)),
+            follow_mode: false,
+            following: false,
+            line_wrapping: LineWrapping::Wrap,
+            horizontal_offset: 0,
+            show_cut_off_indicators: true,
+            search: None,
+            search_match_style: StyleModifier::new()
+                .fg_color(Color::Black)
+                .bg_color(Color::Yellow),
+            active_line_style: None,
+            show_fold_column: true,
+            fold_summary_style: StyleModifier::new().italic(true),
+            filter: None,
+            marks: BTreeMap::new(),
+            jump_list: JumpList::default(),
+            selection_anchor: None,
+            selection_style: StyleModifier::new().bg_color(Color::Blue),
+            cursor_column: None,
+            cursor_style: StyleModifier::new()
+                .invert(BoolModifyMode::Toggle)
+                .underline(true),
+            last_layout: RefCell::new(PagerLayout::default()),
+            mouse_wheel_lines: 3,
         }
     }
 }
 
 impl<L, D> Pager<L, D>
 where
-    L: PagerLine,
+    L: PagerLine + ?Sized,
     D: LineDecorator<Line = L>,
 {
     /// Create an empty pager, with no current content.
     pub fn new() -> Self {
-        Pager {
-            content: None,
-            current_line: LineIndex::new(0),
-        }
+        Self::default()
     }
 
     /// Load (and potentially overwrite previous) content to display in the pager.
     ///
-    /// If possible, the current line position will be preserved.
+    /// If possible, the current line position will be preserved. Marks and entries of the jump
+    /// list that refer to lines that are not present in the new content are removed.
     pub fn load(&mut self, content: PagerContent<L, D>) {
+        {
+            let exists = |line: usize| content.storage.line(line).map(|_| line);
+            update_marks(&mut self.marks, &exists);
+            self.jump_list.update(&exists);
+            self.selection_anchor = self
+                .selection_anchor
+                .and_then(|anchor| exists(anchor.raw_value()))
+                .map(LineIndex::new);
+        }
         self.content = Some(content);
+        if let Some(ref filter) = self.filter {
+            filter.lines_changed(0);
+        }
 
         // Go back to last available line
         let current_line = self.current_line;
-        if !self.line_exists(current_line) {
-            let _ = self.scroll_to_end();
+        if self.following || !self.line_exists(current_line) {
+            let _ = self.go_to_end();
+        }
+        self.skip_to_visible_line();
+    }
+
+    /// Update the active line after the lines in `range` have been replaced by `num_new_lines`
+    /// lines.
+    fn lines_replaced(&mut self, range: Range<usize>, num_new_lines: usize) {
+        if let Some(ref filter) = self.filter {
+            filter.lines_changed(range.start);
+        }
+        self.current_line = LineIndex::new(adjust_index(
+            self.current_line.raw_value(),
+            &range,
+            num_new_lines,
+        ));
+        {
+            let content = self.content.as_ref();
+            let adjust = |line: usize| {
+                let line = adjust_index(line, &range, num_new_lines);
+                content
+                    .and_then(|content| content.storage.line(line))
+                    .map(|_| line)
+            };
+            update_marks(&mut self.marks, &adjust);
+            self.jump_list.update(&adjust);
+            self.selection_anchor = self
+                .selection_anchor
+                .and_then(|anchor| adjust(anchor.raw_value()))
+                .map(LineIndex::new);
+        }
+        let current_line = self.current_line;
+        if self.following || !self.line_exists(current_line) {
+            let _ = self.go_to_end();
+        }
+        self.skip_to_visible_line();
+    }
+
+    /// Move the active line to the first line of the closed fold hiding it, if any.
+    ///
+    /// If the active line does not match the filter, the closest matching line becomes active
+    /// instead.
+    fn skip_to_visible_line(&mut self) {
+        let current_line = self.current_line.raw_value();
+        if let Some(line) = self
+            .visibility()
+            .map(|visibility| visibility.closest_visible(current_line))
+        {
+            self.current_line = LineIndex::new(line);
+        }
+    }
+
+    fn visibility(&self) -> Option<Visibility<'_, L>> {
+        self.content
+            .as_ref()
+            .map(|content| content.visibility(self.filter.as_ref()))
+    }
+
+    /// Only display lines for which the given predicate holds (like `&pattern` in `less`).
+    ///
+    /// Indices of lines (e.g., `current_line_index` or the ones passed to decorators) still refer
+    /// to the lines of the content. If the active line does not match, the closest matching line
+    /// becomes active.
+    pub fn set_filter<F: Fn(LineIndex, &L) -> bool + 'static>(&mut self, predicate: F) {
+        self.filter = Some(LineFilter::new(Box::new(predicate)));
+        self.skip_to_visible_line();
+    }
+
+    /// Only display lines that contain a match of the given regular expression (see
+    /// `set_filter`).
+    pub fn set_filter_pattern(&mut self, pattern: &str) -> Result<(), PagerError> {
+        let regex = Regex::new(pattern).map_err(PagerError::InvalidSearchPattern)?;
+        self.set_filter(move |_, line: &L| regex.is_match(line.get_content()));
+        Ok(())
+    }
+
+    /// Remove the current filter (if any), so that all lines are displayed again.
+    pub fn clear_filter(&mut self) {
+        self.filter = None;
+    }
+
+    /// Temporarily disable (or enable again) the current filter.
+    pub fn set_filter_enabled(&mut self, enabled: bool) {
+        if let Some(ref mut filter) = self.filter {
+            filter.enabled = enabled;
+        }
+        self.skip_to_visible_line();
+    }
+
+    /// Check whether there is a filter and it is enabled.
+    pub fn is_filter_enabled(&self) -> bool {
+        self.filter.as_ref().map(|f| f.enabled).unwrap_or(false)
+    }
+
+    /// Specify whether to draw a column that marks the first lines of open ('-') and closed ('+')
+    /// folds between the decoration and the content, if the content has any folds.
+    pub fn set_fold_column(&mut self, show: bool) {
+        self.show_fold_column = show;
+    }
+
+    /// Set the style of the summary that is drawn after the first line of closed folds.
+    pub fn set_fold_summary_style(&mut self, style: StyleModifier) {
+        self.fold_summary_style = style;
+    }
+
+    /// Open or close the innermost fold containing the active line.
+    pub fn toggle_fold(&mut self) -> Result<(), PagerError> {
+        self.change_fold(|closed| !closed)
+    }
+
+    /// Open the innermost fold containing the active line.
+    pub fn open_fold(&mut self) -> Result<(), PagerError> {
+        self.change_fold(|_| false)
+    }
+
+    /// Close the innermost fold containing the active line. The first line of the fold becomes
+    /// the active line.
+    pub fn close_fold(&mut self) -> Result<(), PagerError> {
+        self.change_fold(|_| true)
+    }
+
+    fn change_fold<F: FnOnce(bool) -> bool>(&mut self, change: F) -> Result<(), PagerError> {
+        {
+            let content = self.content.as_mut().ok_or(PagerError::NoContent)?;
+            let fold = content
+                .folds
+                .innermost_fold(self.current_line)
+                .ok_or(PagerError::NoFold)?;
+            content.folds.set_closed(fold.first, change(fold.closed));
+        }
+        self.skip_to_visible_line();
+        Ok(())
+    }
+
+    /// Open all folds of the current content.
+    pub fn open_all_folds(&mut self) -> Result<(), PagerError> {
+        self.content
+            .as_mut()
+            .ok_or(PagerError::NoContent)?
+            .folds
+            .open_all();
+        Ok(())
+    }
+
+    /// Close all folds of the current content.
+    pub fn close_all_folds(&mut self) -> Result<(), PagerError> {
+        self.content
+            .as_mut()
+            .ok_or(PagerError::NoContent)?
+            .folds
+            .close_all();
+        self.skip_to_visible_line();
+        Ok(())
+    }
+
+    /// Set whether scrolling moves the active line or the viewport (see `ScrollMode`).
+    ///
+    /// The index of the active line is kept, so switching to `ScrollMode::Viewport` will show the
+    /// previously active line at the top of the window.
+    pub fn set_scroll_mode(&mut self, mode: ScrollMode) {
+        self.scroll_mode = mode;
+    }
+
+    /// Get the current scroll mode.
+    pub fn scroll_mode(&self) -> ScrollMode {
+        self.scroll_mode
+    }
+
+    /// Set where the active line is displayed within the window (see `ActiveLinePosition`).
+    pub fn set_active_line_position(&mut self, position: ActiveLinePosition) {
+        self.active_line_position = position;
+    }
+
+    /// Get the current policy for positioning the active line within the window.
+    pub fn active_line_position(&self) -> ActiveLinePosition {
+        self.active_line_position
+    }
+
+    /// Enable or disable follow mode (like `tail -f`).
+    ///
+    /// If enabled, the pager goes to the last line and keeps it active when lines are appended
+    /// (see `append_lines` and `append_ansi_lines`). Moving backwards disengages following, and `scroll_to_end` will
+    /// engage it again. Neither enabling follow mode nor following appended lines is recorded in
+    /// the jump list.
+    ///
+    /// Note that lines cannot be appended to content that is highlighted using
+    /// `PagerContent::with_highlighter`. Use `PagerContent::with_eager_highlighter` or
+    /// `PagerContent::with_lazy_highlighter` for content that is followed, or use
+    /// `append_ansi_lines` for content with ANSI escape sequences.
+    pub fn set_follow_mode(&mut self, enabled: bool) {
+        self.follow_mode = enabled;
+        self.following = false;
+        if enabled {
+            let _ = self.go_to_end();
         }
     }
 
+    /// Check whether follow mode is enabled (but not necessarily engaged).
+    pub fn follow_mode(&self) -> bool {
+        self.follow_mode
+    }
+
+    /// Check whether the pager is currently following the end of the content.
+    pub fn is_following(&self) -> bool {
+        self.following
+    }
+
     /// Clear the current content.
     ///
     /// On subsequent `draw` calls, nothing will be written to the window.
@@ -150,8 +523,8 @@ where
 
     /// Get a mutable reference to the current content, if available.
     ///
-    /// Note that `PagerContent` does not allow mutable access to the stored lines, so it is
-    /// required to use `load` to update the contents. A pager is not a text editor.
+    /// Note that modifying the lines of the content directly will not update the active line. Use
+    /// `append_lines`, `replace_range` and `truncate` of `Pager` instead.
     pub fn content_mut(&mut self) -> Option<&mut PagerContent<L, D>> {
         self.content.as_mut()
     }
@@ -159,7 +532,7 @@ where
     fn line_exists<I: Into<LineIndex>>(&mut self, line: I) -> bool {
         let line: LineIndex = line.into();
         if let Some(ref mut content) = self.content {
-            line.raw_value() < content.storage.len()
+            content.storage.line(line.raw_value()).is_some()
         } else {
             false
         }
@@ -167,10 +540,34 @@ where
 
     /// Go to the specified line, if present.
     ///
-    /// If there is no such line, an error is returned.
+    /// Any closed folds hiding the line are opened. If there is no such line or it does not match
+    /// the current filter, an error is returned. The previously active line is recorded in the
+    /// jump list.
     pub fn go_to_line<I: Into<LineIndex>>(&mut self, line: I) -> Result<(), PagerError> {
-        let line: LineIndex = line.into();
+        let previous_line = self.current_line;
+        self.move_to_line(line.into())?;
+        if self.current_line != previous_line {
+            self.jump_list.record(previous_line.raw_value());
+        }
+        Ok(())
+    }
+
+    /// Go to the specified line (as in `go_to_line`) without recording a jump.
+    fn move_to_line(&mut self, line: LineIndex) -> Result<(), PagerError> {
         if self.line_exists(line) {
+            let filtered_out = self
+                .visibility()
+                .map(|visibility| !visibility.passes_filter(line.raw_value()))
+                .unwrap_or(false);
+            if filtered_out {
+                return Err(PagerError::FilteredOut(line));
+            }
+            if let Some(ref mut content) = self.content {
+                content.folds.reveal(line);
+            }
+            if line < self.current_line {
+                self.following = false;
+            }
             self.current_line = line;
             Ok(())
         } else {
@@ -185,10 +582,12 @@ where
         &mut self,
         predicate: F,
     ) -> Result<(), PagerError> {
-        let line = if let Some(ref mut content) = self.content {
+        let line = if let Some(ref content) = self.content {
+            let visibility = content.visibility(self.filter.as_ref());
             content
                 .view(LineIndex::new(0)..)
-                .find(|&(index, ref line)| predicate(index, line))
+                .filter(|&(index, _)| visibility.passes_filter(index.raw_value()))
+                .find(|&(index, line)| predicate(index, line))
                 .map(|(index, _)| index)
                 .ok_or(PagerError::NoLineWithPredicate)
         } else {
@@ -197,189 +596,1342 @@ where
         line.and_then(|index| self.go_to_line(index))
     }
 
-    /// Get the index of the currently active line.
-    pub fn current_line_index(&self) -> LineIndex {
-        self.current_line
+    /// Go to the last line (as in `scroll_to_end`) without recording a jump.
+    fn go_to_end(&mut self) -> OperationResult {
+        self.following = self.follow_mode;
+        match self.last_possible_line(usize::MAX) {
+            Some(last_line) if last_line != self.current_line.raw_value() => {
+                self.current_line = LineIndex::new(last_line);
+                Ok(())
+            }
+            _ => Err(()),
+        }
     }
 
-    /// Get a reference to the currently active line.
-    pub fn current_line(&self) -> Option<&L> {
-        if let Some(ref content) = self.content {
-            content.storage.get(self.current_line_index().raw_value())
-        } else {
-            None
-        }
+    /// Set the mark with the given name to the active line (replacing any previous position of
+    /// the mark).
+    pub fn set_mark(&mut self, name: char) {
+        self.marks.insert(name, self.current_line.raw_value());
     }
 
-    pub fn as_widget<'a>(&'a self) -> impl Widget + 'a {
-        PagerWidget { inner: self }
+    /// Get the line of the mark with the given name, if it is set.
+    pub fn mark(&self, name: char) -> Option<LineIndex> {
+        self.marks.get(&name).map(|&line| LineIndex::new(line))
     }
-}
 
-struct PagerWidget<'a, L, D>
-where
-    L: PagerLine,
-    D: LineDecorator<Line = L>,
-{
-    inner: &'a Pager<L, D>,
-}
+    /// Remove the mark with the given name.
+    pub fn remove_mark(&mut self, name: char) {
+        self.marks.remove(&name);
+    }
 
-impl<'a, L, D> Widget for PagerWidget<'a, L, D>
-where
-    L: PagerLine,
-    D: LineDecorator<Line = L>,
-{
-    fn space_demand(&self) -> Demand2D {
-        Demand2D {
-            width: Demand::at_least(1),
-            height: Demand::at_least(1),
-        }
+    /// Iterate over the names of all marks along with their lines.
+    pub fn marks(&self) -> impl Iterator<Item = (char, LineIndex)> + '_ {
+        self.marks
+            .iter()
+            .map(|(&name, &line)| (name, LineIndex::new(line)))
     }
-    fn draw(&self, window: Window, _: RenderingHints) {
-        if let Some(ref content) = self.inner.content {
-            let height: Height = window.get_height();
-            // The highlighter might need a minimum number of lines to figure out the syntax:
-            // TODO: make this configurable?
-            let min_highlight_context = 40;
-            let num_adjacent_lines_to_load = max(height.into(), min_highlight_context / 2);
-            let min_line = self
-                .inner
-                .current_line
-                .checked_sub(num_adjacent_lines_to_load)
-                .unwrap_or_else(|| LineIndex::new(0));
-            let max_line = self.inner.current_line + num_adjacent_lines_to_load;
 
-            // Split window
-            let decorator_demand = content
-                .decorator
-                .horizontal_space_demand(content.view(min_line..max_line));
-            let split_pos = layout_linearly(
-                window.get_width(),
-                Width::new(0).unwrap(),
-                &[decorator_demand, Demand::at_least(1)],
-                &[0.0, 1.0],
-            )[0];
+    /// Remove all marks.
+    pub fn clear_marks(&mut self) {
+        self.marks.clear();
+    }
 
-            let (mut decoration_window, mut content_window) = window
-                .split(split_pos.from_origin())
-                .expect("valid split pos");
+    /// Go to the line of the mark with the given name (see `go_to_line`).
+    pub fn go_to_mark(&mut self, name: char) -> Result<(), PagerError> {
+        let line = self.mark(name).ok_or(PagerError::NoMark(name))?;
+        self.go_to_line(line)
+    }
 
-            // Fill background with correct color
-            let bg_style = content.highlight_info.default_style();
-            content_window.set_default_style(bg_style.apply_to_default());
-            content_window.fill(GraphemeCluster::space());
+    /// Go back to the line that was active before the last jump.
+    ///
+    /// If the line is hidden (by a closed fold or the filter), the closest displayed line becomes
+    /// active instead.
+    pub fn jump_back(&mut self) -> Result<(), PagerError> {
+        let current_line = self.current_line.raw_value();
+        let line = self
+            .jump_list
+            .back(current_line)
+            .ok_or(PagerError::NoJump)?;
+        self.return_to_line(line)
+    }
 
-            let mut cursor = Cursor::new(&mut content_window)
-                .position(ColIndex::new(0), RowIndex::new(0))
-                .wrapping_mode(WrappingMode::Wrap);
+    /// Undo the last `jump_back`.
+    pub fn jump_forward(&mut self) -> Result<(), PagerError> {
+        let line = self.jump_list.forward().ok_or(PagerError::NoJump)?;
+        self.return_to_line(line)
+    }
 
-            let num_line_wraps_until_current_line = {
-                content
-                    .view(min_line..self.inner.current_line)
-                    .map(|(_, line)| (cursor.num_expected_wraps(line.get_content()) + 1) as i32)
-                    .sum::<i32>()
-            };
-            let num_line_wraps_from_current_line = {
-                content
-                    .view(self.inner.current_line..max_line)
-                    .map(|(_, line)| (cursor.num_expected_wraps(line.get_content()) + 1) as i32)
-                    .sum::<i32>()
-            };
+    /// Forget all entries of the jump list.
+    pub fn clear_jump_list(&mut self) {
+        self.jump_list.clear();
+    }
 
-            let centered_current_line_start_pos: RowIndex = (height / (2 as usize)).from_origin();
-            let best_current_line_pos_for_bottom = max(
-                centered_current_line_start_pos,
-                height.from_origin() - num_line_wraps_from_current_line,
-            );
-            let required_start_pos = min(
-                RowIndex::new(0),
-                best_current_line_pos_for_bottom - num_line_wraps_until_current_line,
-            );
+    fn return_to_line(&mut self, line: usize) -> Result<(), PagerError> {
+        let line = LineIndex::new(line);
+        if !self.line_exists(line) {
+            return Err(PagerError::NoLineWithIndex(line));
+        }
+        self.following = false;
+        self.current_line = line;
+        self.skip_to_visible_line();
+        Ok(())
+    }
 
-            cursor.move_to(ColIndex::new(0), required_start_pos);
+    /// Start selecting lines at the active line, i.e., set the anchor of the selection to the
+    /// active line (replacing any previous selection).
+    pub fn start_selection(&mut self) {
+        self.selection_anchor = Some(self.current_line);
+    }
 
-            for (line_index, line) in content.view(min_line..max_line) {
-                let line_content = line.get_content();
-                let base_style = if line_index == self.inner.current_line {
-                    StyleModifier::new()
-                        .invert(BoolModifyMode::Toggle)
-                        .bold(true)
-                } else {
-                    StyleModifier::new()
-                };
+    /// Remove the selection.
+    pub fn clear_selection(&mut self) {
+        self.selection_anchor = None;
+    }
 
-                let (_, start_y) = cursor.get_position();
-                let mut last_change_pos = 0;
-                for &(change_pos, style) in content.highlight_info.get_info_for_line(line_index) {
-                    cursor.write(&line_content[last_change_pos..change_pos]);
+    /// Check whether lines are currently being selected.
+    pub fn has_selection(&self) -> bool {
+        self.selection_anchor.is_some()
+    }
 
-                    cursor.set_style_modifier(style.on_top_of(base_style));
-                    last_change_pos = change_pos;
-                }
-                cursor.write(&line_content[last_change_pos..]);
+    /// Get the anchor of the selection, i.e., the line where the selection was started.
+    pub fn selection_anchor(&self) -> Option<LineIndex> {
+        self.selection_anchor
+    }
 
-                cursor.set_style_modifier(base_style);
-                cursor.fill_and_wrap_line();
-                let (_, end_y) = cursor.get_position();
+    /// Get the range of selected lines, which spans from the anchor of the selection to the
+    /// active line (both inclusive).
+    pub fn selection(&self) -> Option<Range<LineIndex>> {
+        self.selection_anchor.map(|anchor| {
+            let (anchor, current_line) = (anchor.raw_value(), self.current_line.raw_value());
+            LineIndex::new(min(anchor, current_line))..LineIndex::new(max(anchor, current_line) + 1)
+        })
+    }
 
-                let range_start_y = min(max(start_y, RowIndex::new(0)), height.from_origin());
-                let range_end_y = min(max(end_y, RowIndex::new(0)), height.from_origin());
-                content.decorator.decorate(
-                    &line,
-                    line_index,
-                    self.inner.current_line,
-                    decoration_window.create_subwindow(.., range_start_y..range_end_y),
-                );
-                //decoration_window.create_subwindow(.., range_start_y..range_end_y).fill('X');
-            }
+    /// Get the indices and contents of all selected lines (including those hidden by closed
+    /// folds or the filter).
+    pub fn selected_lines(&self) -> Vec<(LineIndex, &L)> {
+        match (self.selection(), &self.content) {
+            (Some(range), Some(content)) => content.view(range).collect(),
+            _ => Vec::new(),
         }
     }
-}
 
-impl<L, D> Scrollable for Pager<L, D>
-where
-    L: PagerLine,
-    D: LineDecorator<Line = L>,
-{
-    fn scroll_backwards(&mut self) -> OperationResult {
-        if self.current_line > LineIndex::new(0) {
-            self.current_line -= 1;
-            Ok(())
+    /// Get the contents of all selected lines (see `selected_lines`) joined by newlines.
+    pub fn selected_text(&self) -> Option<String> {
+        self.selection().map(|_| {
+            self.selected_lines()
+                .iter()
+                .map(|&(_, line)| line.get_content())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+    }
+
+    /// Set the style that is applied to selected lines (on top of the highlighting style).
+    pub fn set_selection_style(&mut self, style: StyleModifier) {
+        self.selection_style = style;
+    }
+
+    /// Get the index of the currently active line.
+    ///
+    /// In `ScrollMode::Viewport` this is the top line of the viewport.
+    pub fn current_line_index(&self) -> LineIndex {
+        self.current_line
+    }
+
+    /// Get a reference to the currently active line.
+    pub fn current_line(&self) -> Option<&L> {
+        if let Some(ref content) = self.content {
+            content.storage.line(self.current_line_index().raw_value())
         } else {
-            Err(())
+            None
         }
     }
-    fn scroll_forwards(&mut self) -> OperationResult {
-        let new_line = self.current_line + 1;
-        self.go_to_line(new_line).map_err(|_| ())
-    }
-    fn scroll_to_beginning(&mut self) -> OperationResult {
-        if self.current_line == LineIndex::new(0) {
+
+    /// Move the active line (or the viewport) backwards by the specified number of lines.
+    ///
+    /// Lines hidden by closed folds or the filter are skipped.
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_backwards_by(&mut self, lines: usize) -> OperationResult {
+        let current_line = self.current_line.raw_value();
+        let new_line = match self.visibility() {
+            Some(visibility) => visibility.nth_visible_before(current_line, lines),
+            None => current_line.saturating_sub(lines),
+        };
+        if new_line >= current_line {
             Err(())
         } else {
-            self.current_line = LineIndex::new(0);
+            self.following = false;
+            self.current_line = LineIndex::new(new_line);
             Ok(())
         }
     }
-    fn scroll_to_end(&mut self) -> OperationResult {
-        if let Some(ref content) = self.content {
-            if content.storage.is_empty() {
-                return Err(());
-            }
-            let last_line = LineIndex::new(content.storage.len() - 1);
-            if self.current_line == last_line {
-                Err(())
-            } else {
-                self.current_line = last_line;
+
+    /// Move the active line (or the viewport) forwards by the specified number of lines.
+    ///
+    /// In `ScrollMode::Viewport`, the viewport is not moved beyond the point where the last line
+    /// is at the bottom of the window.
+    ///
+    /// Lines hidden by closed folds or the filter are skipped.
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_forwards_by(&mut self, lines: usize) -> OperationResult {
+        let current_line = self.current_line.raw_value();
+        let target_line = match self.visibility() {
+            Some(visibility) => visibility.nth_visible_after(current_line, lines),
+            None => current_line.saturating_add(lines),
+        };
+        match self.last_possible_line(target_line) {
+            Some(new_line) if new_line > current_line => {
+                self.current_line = LineIndex::new(new_line);
                 Ok(())
             }
-        } else {
-            Err(())
+            _ => Err(()),
         }
     }
-}
 
-/// Anything that represents a single line in a pager. Other than the main content (something
+    /// Move the active line (or the viewport) backwards by the height of the window (as of the
+    /// last `draw`).
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_page_backwards(&mut self) -> OperationResult {
+        let lines = self.page_height();
+        self.scroll_backwards_by(lines)
+    }
+
+    /// Move the active line (or the viewport) forwards by the height of the window (as of the last
+    /// `draw`).
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_page_forwards(&mut self) -> OperationResult {
+        let lines = self.page_height();
+        self.scroll_forwards_by(lines)
+    }
+
+    /// Move the active line (or the viewport) backwards by half the height of the window (as of
+    /// the last `draw`).
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_half_page_backwards(&mut self) -> OperationResult {
+        let lines = max(self.page_height() / 2, 1);
+        self.scroll_backwards_by(lines)
+    }
+
+    /// Move the active line (or the viewport) forwards by half the height of the window (as of
+    /// the last `draw`).
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_half_page_forwards(&mut self) -> OperationResult {
+        let lines = max(self.page_height() / 2, 1);
+        self.scroll_forwards_by(lines)
+    }
+
+    fn page_height(&self) -> usize {
+        max(self.last_layout.borrow().height, 1)
+    }
+
+    /// Get the line closest to (but not after) `line` that may be the active line (or the top line
+    /// of the viewport), or `None` if there is no content.
+    fn last_possible_line(&self, line: usize) -> Option<usize> {
+        let content = self.content.as_ref()?;
+        let storage = &content.storage;
+        let lines_below = match self.scroll_mode {
+            ScrollMode::ActiveLine => 1,
+            ScrollMode::Viewport => self.page_height(),
+        };
+        // Avoid determining the length of (lazy) sources unless the end is close.
+        let line = if storage.line(line.saturating_add(lines_below - 1)).is_some() {
+            line
+        } else if storage.is_empty() {
+            return None;
+        } else {
+            storage.len().saturating_sub(lines_below)
+        };
+        Some(
+            content
+                .visibility(self.filter.as_ref())
+                .closest_visible(line),
+        )
+    }
+
+    /// Set how lines that are wider than the window are displayed.
+    ///
+    /// Switching to `LineWrapping::Wrap` resets the horizontal scroll position.
+    pub fn set_line_wrapping(&mut self, wrapping: LineWrapping) {
+        self.line_wrapping = wrapping;
+        if wrapping == LineWrapping::Wrap {
+            self.horizontal_offset = 0;
+        }
+    }
+
+    /// Get the current way of displaying lines that are wider than the window.
+    pub fn line_wrapping(&self) -> LineWrapping {
+        self.line_wrapping
+    }
+
+    /// Specify whether to draw indicators ('<' and '>') at the window borders for lines that are
+    /// cut off due to `LineWrapping::Truncate`.
+    pub fn set_cut_off_indicators(&mut self, show: bool) {
+        self.show_cut_off_indicators = show;
+    }
+
+    /// Get the number of columns that are currently hidden at the left of each line.
+    pub fn horizontal_offset(&self) -> usize {
+        self.horizontal_offset
+    }
+
+    /// Move the visible part of all lines to the left by the specified number of columns.
+    ///
+    /// This fails if lines are wrapped or the beginning of the lines is already visible.
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_left(&mut self, columns: usize) -> OperationResult {
+        if self.line_wrapping == LineWrapping::Wrap || self.horizontal_offset == 0 {
+            Err(())
+        } else {
+            self.horizontal_offset = self.horizontal_offset.saturating_sub(columns);
+            Ok(())
+        }
+    }
+
+    /// Move the visible part of all lines to the right by the specified number of columns.
+    ///
+    /// The end of the widest visible line (as of the last `draw`) is not moved further left than
+    /// the right border of the window. This fails if lines are wrapped or the end of all visible
+    /// lines is already visible.
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_right(&mut self, columns: usize) -> OperationResult {
+        let max_offset = self.max_horizontal_offset();
+        if self.line_wrapping == LineWrapping::Wrap || self.horizontal_offset >= max_offset {
+            Err(())
+        } else {
+            self.horizontal_offset = min(self.horizontal_offset + columns, max_offset);
+            Ok(())
+        }
+    }
+
+    fn max_horizontal_offset(&self) -> usize {
+        let content = match self.content {
+            Some(ref content) => content,
+            None => return 0,
+        };
+        let layout = self.last_layout.borrow();
+        let widest_line = layout
+            .lines
+            .iter()
+            .filter_map(|&(line, _)| content.view_line(line))
+            .map(|line| text::width(line.get_content()))
+            .max()
+            .unwrap_or(0);
+        widest_line.saturating_sub(layout.content_width)
+    }
+
+    /// Move the visible part of all lines to the left by half the width of the window (as of the
+    /// last `draw`).
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_left_half_screen(&mut self) -> OperationResult {
+        let columns = self.half_screen_width();
+        self.scroll_left(columns)
+    }
+
+    /// Move the visible part of all lines to the right by half the width of the window (as of the
+    /// last `draw`).
+    #[allow(clippy::result_unit_err)]
+    pub fn scroll_right_half_screen(&mut self) -> OperationResult {
+        let columns = self.half_screen_width();
+        self.scroll_right(columns)
+    }
+
+    fn half_screen_width(&self) -> usize {
+        max(self.last_layout.borrow().content_width / 2, 1)
+    }
+
+    /// Enable (at the beginning of the line) or disable the column cursor within the active line.
+    pub fn set_column_cursor(&mut self, enabled: bool) {
+        self.cursor_column = if enabled {
+            Some(self.cursor_column.unwrap_or(0))
+        } else {
+            None
+        };
+    }
+
+    /// Check whether the column cursor is enabled.
+    pub fn has_column_cursor(&self) -> bool {
+        self.cursor_column.is_some()
+    }
+
+    /// Set the style that is applied (on top of all other styles) to the grapheme cluster under
+    /// the column cursor.
+    pub fn set_cursor_style(&mut self, style: StyleModifier) {
+        self.cursor_style = style;
+    }
+
+    /// Get the display column at which the grapheme cluster under the column cursor starts, if
+    /// the cursor is enabled.
+    pub fn cursor_column(&self) -> Option<usize> {
+        let column = self.cursor_column?;
+        let line = self.current_line().map(|l| l.get_content()).unwrap_or("");
+        Some(text::grapheme_at_column(line, column).1)
+    }
+
+    /// Get the byte range of the grapheme cluster under the column cursor in the active line, if
+    /// the cursor is enabled.
+    ///
+    /// If the active line is narrower than the (preferred) column of the cursor, the cursor is
+    /// placed on the last grapheme cluster of the line.
+    pub fn cursor_range(&self) -> Option<Range<usize>> {
+        let column = self.cursor_column?;
+        let line = self.current_line()?.get_content();
+        Some(text::grapheme_at_column(line, column).0)
+    }
+
+    /// Move the column cursor one grapheme cluster to the left.
+    #[allow(clippy::result_unit_err)]
+    pub fn cursor_left(&mut self) -> OperationResult {
+        let target = {
+            let line = self.current_line().ok_or(())?.get_content();
+            let range = self.cursor_range().ok_or(())?;
+            text::previous_grapheme_pos(line, range.start).ok_or(())?
+        };
+        self.move_cursor_to(target)
+    }
+
+    /// Move the column cursor one grapheme cluster to the right.
+    #[allow(clippy::result_unit_err)]
+    pub fn cursor_right(&mut self) -> OperationResult {
+        let target = {
+            let line = self.current_line().ok_or(())?.get_content();
+            let range = self.cursor_range().ok_or(())?;
+            if range.end >= line.len() {
+                return Err(());
+            }
+            range.end
+        };
+        self.move_cursor_to(target)
+    }
+
+    /// Move the column cursor to the beginning of the next word in the active line.
+    #[allow(clippy::result_unit_err)]
+    pub fn cursor_word_forwards(&mut self) -> OperationResult {
+        let target = {
+            let line = self.current_line().ok_or(())?.get_content();
+            let range = self.cursor_range().ok_or(())?;
+            text::words(line)
+                .map(|word| word.start)
+                .find(|&start| start > range.start)
+                .ok_or(())?
+        };
+        self.move_cursor_to(target)
+    }
+
+    /// Move the column cursor to the beginning of the current (or, if it is already there, the
+    /// previous) word in the active line.
+    #[allow(clippy::result_unit_err)]
+    pub fn cursor_word_backwards(&mut self) -> OperationResult {
+        let target = {
+            let line = self.current_line().ok_or(())?.get_content();
+            let range = self.cursor_range().ok_or(())?;
+            text::words(line)
+                .map(|word| word.start)
+                .take_while(|&start| start < range.start)
+                .last()
+                .ok_or(())?
+        };
+        self.move_cursor_to(target)
+    }
+
+    /// Move the column cursor to the beginning of the active line.
+    #[allow(clippy::result_unit_err)]
+    pub fn cursor_to_line_start(&mut self) -> OperationResult {
+        match self.cursor_range() {
+            Some(ref range) if range.start > 0 => self.move_cursor_to(0),
+            _ => Err(()),
+        }
+    }
+
+    /// Move the column cursor to the last grapheme cluster of the active line.
+    #[allow(clippy::result_unit_err)]
+    pub fn cursor_to_line_end(&mut self) -> OperationResult {
+        let target = {
+            let line = self.current_line().ok_or(())?.get_content();
+            let range = self.cursor_range().ok_or(())?;
+            match text::previous_grapheme_pos(line, line.len()) {
+                Some(pos) if pos > range.start => pos,
+                _ => return Err(()),
+            }
+        };
+        self.move_cursor_to(target)
+    }
+
+    /// Move the column cursor to the grapheme cluster starting at byte position `pos` of the
+    /// active line and make sure that it is visible if lines are truncated.
+    fn move_cursor_to(&mut self, pos: usize) -> OperationResult {
+        let (column, cursor_width) = {
+            let line = self.current_line().ok_or(())?.get_content();
+            let (range, column) = text::grapheme_at_column(line, text::width(&line[..pos]));
+            (column, max(text::width(&line[..range.end]) - column, 1))
+        };
+        self.cursor_column = Some(column);
+        if self.line_wrapping == LineWrapping::Truncate {
+            let width = max(self.last_layout.borrow().content_width, 1);
+            if column < self.horizontal_offset {
+                self.horizontal_offset = column;
+            } else if column + cursor_width > self.horizontal_offset + width {
+                self.horizontal_offset = (column + cursor_width).saturating_sub(width);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the word (according to the Unicode word boundaries) under the column cursor, if any.
+    pub fn word_under_cursor(&self) -> Option<&str> {
+        let line = self.current_line()?.get_content();
+        let range = self.cursor_range()?;
+        text::words(line)
+            .find(|word| word.start <= range.start && range.start < word.end)
+            .map(|word| &line[word])
+    }
+
+    /// Get the whitespace-delimited token (e.g., a URL or path) under the column cursor, if any.
+    pub fn token_under_cursor(&self) -> Option<&str> {
+        let line = self.current_line()?.get_content();
+        let range = self.cursor_range()?;
+        text::token_at(line, range.start).map(|token| &line[token])
+    }
+
+    /// Get the layout of the last draw, e.g., which lines were visible and on which rows they were
+    /// drawn.
+    ///
+    /// The layout is empty if the pager has not been drawn yet or had no content when it was
+    /// drawn last.
+    ///
+    /// The layout is updated when drawing the pager, so the returned reference must not be held
+    /// while the pager is drawn.
+    pub fn layout(&self) -> Ref<'_, PagerLayout> {
+        self.last_layout.borrow()
+    }
+
+    /// Set the number of lines to scroll per mouse wheel event.
+    pub fn set_mouse_wheel_lines(&mut self, lines: usize) {
+        self.mouse_wheel_lines = lines;
+    }
+
+    /// Handle a mouse event (as delivered by `unsegen`).
+    ///
+    /// `window_origin` is the position of the top left corner of the window that the pager was
+    /// last drawn to, relative to the top left corner of the terminal. Events outside of the window
+    /// are ignored.
+    #[allow(clippy::result_unit_err)]
+    pub fn handle_mouse_event(
+        &mut self,
+        event: MouseEvent,
+        window_origin: (ColIndex, RowIndex),
+    ) -> OperationResult {
+        // Positions of mouse events start at (1, 1).
+        let position = |x: u16, y: u16| {
+            (
+                ColIndex::new(i32::from(x) - 1 - window_origin.0.raw_value()),
+                RowIndex::new(i32::from(y) - 1 - window_origin.1.raw_value()),
+            )
+        };
+        match event {
+            MouseEvent::Press(MouseButton::WheelUp, _, _) => {
+                let lines = self.mouse_wheel_lines;
+                self.scroll_backwards_by(lines)
+            }
+            MouseEvent::Press(MouseButton::WheelDown, _, _) => {
+                let lines = self.mouse_wheel_lines;
+                self.scroll_forwards_by(lines)
+            }
+            MouseEvent::Press(button, x, y) => {
+                let (column, row) = position(x, y);
+                self.click(button, column, row)
+            }
+            // Dragging moves the active line along with the mouse.
+            MouseEvent::Hold(x, y) => {
+                let (column, row) = position(x, y);
+                let decoration_width = self.last_layout.borrow().decoration_width;
+                if column < ColIndex::new(decoration_width as i32) {
+                    return Err(());
+                }
+                self.click(MouseButton::Left, column, row)
+            }
+            MouseEvent::Release(_, _) => Err(()),
+        }
+    }
+
+    /// Handle a mouse event on a `Scrollbar` that is drawn next to the pager (with the same
+    /// height).
+    ///
+    /// `scrollbar_origin` is the position of the top of the scrollbar relative to the top left
+    /// corner of the terminal. Clicking or dragging moves the visible part of the content to the
+    /// corresponding position, the mouse wheel scrolls the content.
+    #[allow(clippy::result_unit_err)]
+    pub fn handle_scrollbar_mouse_event(
+        &mut self,
+        event: MouseEvent,
+        scrollbar_origin: (ColIndex, RowIndex),
+    ) -> OperationResult {
+        let y = match event {
+            MouseEvent::Press(MouseButton::Left, _, y) | MouseEvent::Hold(_, y) => y,
+            MouseEvent::Press(MouseButton::WheelUp, _, _)
+            | MouseEvent::Press(MouseButton::WheelDown, _, _) => {
+                return self.handle_mouse_event(event, scrollbar_origin);
+            }
+            _ => return Err(()),
+        };
+        let height = self.layout().height;
+        // Positions of mouse events start at (1, 1).
+        let row = i32::from(y) - 1 - scrollbar_origin.1.raw_value();
+        if height == 0 || row < 0 || row as usize >= height {
+            return Err(());
+        }
+        let num_lines = self.content.as_ref().ok_or(())?.num_lines();
+        let target = row as usize * num_lines / height;
+        let line = self.last_possible_line(target).ok_or(())?;
+        self.following = false;
+        self.current_line = LineIndex::new(line);
+        Ok(())
+    }
+
+    /// Handle a click at the given position relative to the top left corner of the window that
+    /// the pager was last drawn to.
+    ///
+    /// Clicks on the decoration of a line are passed to `LineDecorator::click`, clicks on the
+    /// fold column open or close the fold starting at the line. Left clicks on a line make it
+    /// active (unless in `ScrollMode::Viewport`) and move the column cursor (if enabled) to the
+    /// clicked position.
+    #[allow(clippy::result_unit_err)]
+    pub fn click(
+        &mut self,
+        button: MouseButton,
+        column: ColIndex,
+        row: RowIndex,
+    ) -> OperationResult {
+        let layout = self.layout().clone();
+        let (line, rows) = layout.line_at_row(row).ok_or(())?;
+        if column < ColIndex::new(0) || column >= ColIndex::new(layout.width() as i32) {
+            return Err(());
+        }
+        let column = column.raw_value() as usize;
+        if column < layout.decoration_width {
+            let content = self.content.as_mut().ok_or(())?;
+            let line_content = content.storage.line(line.raw_value()).ok_or(())?;
+            content.decorator.click(
+                line_content,
+                line,
+                ColIndex::new(column as i32),
+                Width::new(layout.decoration_width as i32).unwrap(),
+                button,
+            );
+            return Ok(());
+        }
+        let column = column - layout.decoration_width;
+        if column < layout.fold_column_width {
+            {
+                let content = self.content.as_mut().ok_or(())?;
+                let fold = content.folds.fold_starting_at(line).ok_or(())?;
+                content.folds.set_closed(fold.first, !fold.closed);
+            }
+            self.skip_to_visible_line();
+            return Ok(());
+        }
+        let column = column - layout.fold_column_width;
+        if button != MouseButton::Left || self.scroll_mode == ScrollMode::Viewport {
+            return Err(());
+        }
+        self.move_to_line(line).map_err(|_| ())?;
+        if self.cursor_column.is_some() {
+            let column = if self.line_wrapping == LineWrapping::Truncate {
+                self.horizontal_offset + column
+            } else {
+                let line_content = self.current_line().map(|l| l.get_content()).unwrap_or("");
+                text::column_at_wrapped_position(
+                    line_content,
+                    layout.content_width,
+                    (row - rows.start).raw_value() as usize,
+                    column,
+                )
+            };
+            self.cursor_column = Some(column);
+        }
+        Ok(())
+    }
+
+    /// Set the regular expression to search for in the current (and any future) content.
+    ///
+    /// All matches of the pattern are highlighted when drawing. The active line is not changed, use
+    /// `search_next`, `search_previous` or `incremental_search` to jump to a match.
+    pub fn set_search_pattern(&mut self, pattern: &str) -> Result<(), PagerError> {
+        let regex = Regex::new(pattern).map_err(PagerError::InvalidSearchPattern)?;
+        self.search = Some(regex);
+        Ok(())
+    }
+
+    /// Remove the current search pattern (if any) and thus the highlighting of matches.
+    pub fn clear_search(&mut self) {
+        self.search = None;
+    }
+
+    /// Get the currently active search pattern, if present.
+    pub fn search_pattern(&self) -> Option<&str> {
+        self.search.as_ref().map(|regex| regex.as_str())
+    }
+
+    /// Set the style that is applied (on top of the highlighting style) to all search matches.
+    pub fn set_search_match_style(&mut self, style: StyleModifier) {
+        self.search_match_style = style;
+    }
+
+    /// Set the style of the active line, which is applied on top of the highlighting style and
+    /// also covers the empty part of the line up to the window border.
+    ///
+    /// If `None` is specified, the style suggested by the highlighter (e.g., derived from the
+    /// `line_highlight` setting of the theme of a `SyntectHighlighter`) is used, or inverted bold
+    /// text if there is no suggestion.
+    pub fn set_active_line_style(&mut self, style: Option<StyleModifier>) {
+        self.active_line_style = style;
+    }
+
+    /// Set a new search pattern and go to the first match at or after `origin`, wrapping around
+    /// at the end of the content.
+    ///
+    /// This is meant to be called repeatedly with the same `origin` while the user is typing the
+    /// pattern. If there is no match (or the pattern is invalid), the active line is reset to
+    /// `origin` and an error is returned.
+    pub fn incremental_search<I: Into<LineIndex>>(
+        &mut self,
+        pattern: &str,
+        origin: I,
+    ) -> Result<(), PagerError> {
+        let origin: LineIndex = origin.into();
+        let _ = self.move_to_line(origin);
+        self.set_search_pattern(pattern)?;
+        let line = self.find_match(origin, true, false)?;
+        self.go_to_line(line)
+    }
+
+    /// Go to the next line (after the active line) that contains a match of the current search
+    /// pattern, wrapping around at the end of the content.
+    pub fn search_next(&mut self) -> Result<(), PagerError> {
+        let current_line = self.current_line;
+        let line = self.find_match(current_line, true, true)?;
+        self.go_to_line(line)
+    }
+
+    /// Go to the previous line (before the active line) that contains a match of the current
+    /// search pattern, wrapping around at the beginning of the content.
+    pub fn search_previous(&mut self) -> Result<(), PagerError> {
+        let current_line = self.current_line;
+        let line = self.find_match(current_line, false, true)?;
+        self.go_to_line(line)
+    }
+
+    /// Count all matches of the current search pattern in the content.
+    ///
+    /// Note that this requires scanning the whole content. The matches are remembered until the
+    /// content or the search pattern changes.
+    pub fn search_match_count(&self) -> usize {
+        match (&self.search, &self.content) {
+            (Some(regex), Some(content)) => content.search_matches(regex).count,
+            _ => 0,
+        }
+    }
+
+    /// Get the indices of all lines that contain a match of the current search pattern.
+    ///
+    /// Note that this requires scanning the whole content. The matches are remembered until the
+    /// content or the search pattern changes.
+    pub fn search_match_lines(&self) -> Vec<LineIndex> {
+        match (&self.search, &self.content) {
+            (Some(regex), Some(content)) => content.search_matches(regex).lines.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Find the first line (in the specified direction) that contains a search match, starting at
+    /// `start` (or the line after it if `skip_start` is set) and wrapping around.
+    fn find_match(
+        &self,
+        start: LineIndex,
+        forwards: bool,
+        skip_start: bool,
+    ) -> Result<LineIndex, PagerError> {
+        let regex = self.search.as_ref().ok_or(PagerError::NoSearchPattern)?;
+        let content = self.content.as_ref().ok_or(PagerError::NoContent)?;
+        let visibility = content.visibility(self.filter.as_ref());
+        let is_match = |&(index, line): &(LineIndex, &L)| {
+            visibility.passes_filter(index.raw_value())
+                && non_empty_matches(regex, line.get_content())
+                    .next()
+                    .is_some()
+        };
+        let found = match (forwards, skip_start) {
+            (true, true) => content
+                .view(start + 1..)
+                .chain(content.view(..=start))
+                .find(is_match),
+            (true, false) => content
+                .view(start..)
+                .chain(content.view(..start))
+                .find(is_match),
+            (false, true) => content
+                .view(..start)
+                .rev()
+                .chain(content.view(start..).rev())
+                .find(is_match),
+            (false, false) => content
+                .view(..=start)
+                .rev()
+                .chain(content.view(start + 1..).rev())
+                .find(is_match),
+        };
+        found.map(|(index, _)| index).ok_or(PagerError::NoMatch)
+    }
+
+    /// Byte ranges of all (non-empty) search matches in the given line.
+    fn search_matches(&self, line: &str) -> Vec<Range<usize>> {
+        if let Some(ref regex) = self.search {
+            non_empty_matches(regex, line).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn as_widget<'a>(&'a self) -> impl Widget + 'a {
+        PagerWidget { inner: self }
+    }
+
+    /// Create a `StatusLine` widget that displays information about the pager (e.g., the active
+    /// line and the name of the content).
+    pub fn status_line(&self) -> StatusLine<'_, L, D> {
+        StatusLine::new(self)
+    }
+
+    /// Create a `Scrollbar` widget that indicates which part of the content is visible.
+    pub fn scrollbar(&self) -> Scrollbar<'_, L, D> {
+        Scrollbar::new(self)
+    }
+}
+
+impl<L, D> Pager<L, D>
+where
+    L: PagerLine,
+    D: LineDecorator<Line = L>,
+{
+    /// Append lines to the end of the current content (see `PagerContent::append_lines`).
+    ///
+    /// In contrast to `PagerContent::append_lines`, this will keep the last line active if the
+    /// pager is currently following the content (see `set_follow_mode`).
+    ///
+    /// As for `PagerContent::append_lines`, this fails with `PagerError::FixedHighlighting` if the
+    /// content is highlighted using `PagerContent::with_highlighter`.
+    pub fn append_lines<I: IntoIterator<Item = L>>(&mut self, lines: I) -> Result<(), PagerError> {
+        self.content
+            .as_mut()
+            .ok_or(PagerError::NoContent)?
+            .append_lines(lines)?;
+        if self.following {
+            let _ = self.go_to_end();
+        }
+        Ok(())
+    }
+
+    /// Replace the lines of the current content in the specified range by the given lines (see
+    /// `PagerContent::replace_range`).
+    ///
+    /// The active line is moved along if lines before it are inserted or removed. If the active
+    /// line itself is replaced, the first replacing line becomes active.
+    pub fn replace_range<I, R, N>(&mut self, range: R, lines: N) -> Result<(), PagerError>
+    where
+        I: Into<LineIndex> + Clone,
+        R: RangeBounds<I>,
+        N: IntoIterator<Item = L>,
+    {
+        let (range, num_new_lines) = {
+            let content = self.content.as_mut().ok_or(PagerError::NoContent)?;
+            let range = content.resolve_range(range);
+            let num_new_lines = content.splice(range.clone(), lines)?;
+            (range, num_new_lines)
+        };
+        self.lines_replaced(range, num_new_lines);
+        Ok(())
+    }
+
+    /// Remove all lines of the current content after the first `num_lines` lines (see
+    /// `PagerContent::truncate`).
+    pub fn truncate(&mut self, num_lines: usize) -> Result<(), PagerError> {
+        self.replace_range(LineIndex::new(num_lines).., None)
+    }
+}
+
+impl<D> Pager<String, D>
+where
+    D: LineDecorator<Line = String>,
+{
+    /// Append lines that contain ANSI escape sequences to the end of the current content (see
+    /// `PagerContent::append_ansi_lines`).
+    ///
+    /// As for `append_lines`, the last line is kept active if the pager is currently following
+    /// the content (see `set_follow_mode`).
+    pub fn append_ansi_lines<S: AsRef<str>, I: IntoIterator<Item = S>>(
+        &mut self,
+        lines: I,
+    ) -> Result<(), PagerError> {
+        self.content
+            .as_mut()
+            .ok_or(PagerError::NoContent)?
+            .append_ansi_lines(lines)?;
+        if self.following {
+            let _ = self.go_to_end();
+        }
+        Ok(())
+    }
+}
+
+struct PagerWidget<'a, L, D>
+where
+    L: PagerLine + ?Sized,
+    D: LineDecorator<Line = L>,
+{
+    inner: &'a Pager<L, D>,
+}
+
+impl<'a, L, D> Widget for PagerWidget<'a, L, D>
+where
+    L: PagerLine + ?Sized,
+    D: LineDecorator<Line = L>,
+{
+    fn space_demand(&self) -> Demand2D {
+        Demand2D {
+            width: Demand::at_least(1),
+            height: Demand::at_least(1),
+        }
+    }
+    fn draw(&self, window: Window, _: RenderingHints) {
+        if let Some(ref content) = self.inner.content {
+            let height: Height = window.get_height();
+            // The highlighter might need a minimum number of lines to figure out the syntax:
+            // TODO: make this configurable?
+            let min_highlight_context = 40;
+            let num_adjacent_lines_to_load = max(height.into(), min_highlight_context / 2);
+            let folds = &content.folds;
+            let visibility = &content.visibility(self.inner.filter.as_ref());
+            let current_line = self.inner.current_line;
+            let min_line = LineIndex::new(
+                visibility.nth_visible_before(current_line.raw_value(), num_adjacent_lines_to_load),
+            );
+            let max_line = LineIndex::new(
+                visibility.nth_visible_after(current_line.raw_value(), num_adjacent_lines_to_load),
+            );
+            let visible_lines = |range: Range<LineIndex>| {
+                content
+                    .view(range)
+                    .filter(move |&(index, _)| visibility.is_visible(index.raw_value()))
+            };
+
+            // Split window
+            let decorator_demand = content
+                .decorator
+                .horizontal_space_demand(visible_lines(min_line..max_line));
+            let split_pos = layout_linearly(
+                window.get_width(),
+                Width::new(0).unwrap(),
+                &[decorator_demand, Demand::at_least(1)],
+                &[0.0, 1.0],
+            )[0];
+
+            let (mut decoration_window, content_window) = window
+                .split(split_pos.from_origin())
+                .expect("valid split pos");
+
+            let content_width: usize = content_window.get_width().into();
+            let fold_column_width =
+                if self.inner.show_fold_column && !folds.is_empty() && content_width > 1 {
+                    1
+                } else {
+                    0
+                };
+            let (mut fold_window, mut content_window) = content_window
+                .split(ColIndex::new(fold_column_width))
+                .expect("valid split pos");
+            let mut layout = PagerLayout {
+                lines: Vec::new(),
+                decoration_width: split_pos.into(),
+                fold_column_width: fold_column_width as usize,
+                content_width: content_width - fold_column_width as usize,
+                height: height.into(),
+            };
+
+            // Only highlight lines that are not hidden by closed folds or the filter.
+            let mut visible_ranges: Vec<Range<usize>> = Vec::new();
+            for (index, _) in visible_lines(min_line..max_line) {
+                let index = index.raw_value();
+                match visible_ranges.last_mut() {
+                    Some(range) if range.end == index => range.end += 1,
+                    _ => visible_ranges.push(index..index + 1),
+                }
+            }
+            let mut highlighting = content.highlighting.borrow_mut();
+            let get_line_content = |i| {
+                content
+                    .view_line(LineIndex::new(i))
+                    .map(|line| line.get_content())
+            };
+            for range in visible_ranges {
+                highlighting.prepare(range, &get_line_content);
+            }
+
+            // Fill background with correct color
+            let bg_style = highlighting.default_style();
+            content_window.set_default_style(bg_style.apply_to_default());
+            content_window.fill(GraphemeCluster::space());
+
+            let active_line_style = self
+                .inner
+                .active_line_style
+                .or_else(|| highlighting.active_line_style())
+                .unwrap_or_else(|| {
+                    StyleModifier::new()
+                        .invert(BoolModifyMode::Toggle)
+                        .bold(true)
+                });
+
+            let selection = self.inner.selection();
+
+            let width: usize = content_window.get_width().into();
+            let viewport = self.inner.scroll_mode == ScrollMode::Viewport;
+            let truncate = self.inner.line_wrapping == LineWrapping::Truncate;
+
+            let mut cursor = Cursor::new(&mut content_window)
+                .position(ColIndex::new(0), RowIndex::new(0))
+                .wrapping_mode(if truncate {
+                    WrappingMode::NoWrap
+                } else {
+                    WrappingMode::Wrap
+                });
+
+            let num_rows = |line: &L| {
+                if truncate {
+                    1
+                } else {
+                    text::num_wrapped_rows(line.get_content(), width) as i32
+                }
+            };
+            let num_line_wraps_until_current_line = {
+                visible_lines(min_line..current_line)
+                    .map(|(_, line)| num_rows(line))
+                    .sum::<i32>()
+            };
+            let num_line_wraps_from_current_line = {
+                visible_lines(current_line..max_line)
+                    .map(|(_, line)| num_rows(line))
+                    .sum::<i32>()
+            };
+
+            let centered_current_line_start_pos: RowIndex = (height / 2usize).from_origin();
+            let best_current_line_pos_for_bottom = max(
+                centered_current_line_start_pos,
+                height.from_origin() - num_line_wraps_from_current_line,
+            );
+            let num_rows_in = |range: Range<LineIndex>| {
+                visible_lines(range)
+                    .map(|(_, line)| num_rows(line))
+                    .sum::<i32>()
+            };
+            let current_line_pos = if viewport {
+                RowIndex::new(0)
+            } else {
+                match self.inner.active_line_position {
+                    ActiveLinePosition::Centered => best_current_line_pos_for_bottom,
+                    ActiveLinePosition::PinnedTop => RowIndex::new(0),
+                    ActiveLinePosition::PinnedBottom => {
+                        height.from_origin() - num_rows_in(current_line..current_line + 1)
+                    }
+                    ActiveLinePosition::ScrollOff(context) => {
+                        // Keep the active line where it was in the last draw (relative to the
+                        // previous top line), unless it would come too close to an edge.
+                        let previous_top = self.inner.viewport_top.get();
+                        let (previous_top_raw, current_line_raw) =
+                            (previous_top.raw_value(), current_line.raw_value());
+                        let window_rows: usize = height.into();
+                        let previous_pos = if visibility
+                            .nth_visible_after(min(previous_top_raw, current_line_raw), window_rows)
+                            < max(previous_top_raw, current_line_raw)
+                        {
+                            // Jumped far away: Start with a centered line.
+                            centered_current_line_start_pos
+                        } else if previous_top <= current_line {
+                            RowIndex::new(num_rows_in(previous_top..current_line))
+                        } else {
+                            RowIndex::new(-num_rows_in(current_line..previous_top))
+                        };
+                        let highest_pos = RowIndex::new(num_rows_in(
+                            LineIndex::new(visibility.nth_visible_before(current_line_raw, context))
+                                ..current_line,
+                        ));
+                        let lowest_pos = height.from_origin()
+                            - num_rows_in(
+                                current_line
+                                    ..LineIndex::new(
+                                        visibility.nth_visible_after(current_line_raw, context + 1),
+                                    ),
+                            );
+                        let pos = if highest_pos > lowest_pos {
+                            // Window is too small to show all context lines
+                            centered_current_line_start_pos
+                        } else {
+                            min(max(previous_pos, highest_pos), lowest_pos)
+                        };
+                        // Do not leave empty rows at the bottom if there is content to fill them.
+                        max(pos, height.from_origin() - num_line_wraps_from_current_line)
+                    }
+                }
+            };
+            let required_start_pos = min(
+                RowIndex::new(0),
+                current_line_pos - num_line_wraps_until_current_line,
+            );
+
+            cursor.move_to(ColIndex::new(0), required_start_pos);
+
+            for (line_index, line) in visible_lines(min_line..max_line) {
+                let line_content = line.get_content();
+                let selection_style = match selection {
+                    Some(ref range) if range.start <= line_index && line_index < range.end => {
+                        self.inner.selection_style
+                    }
+                    _ => StyleModifier::new(),
+                };
+                let base_style = if line_index == self.inner.current_line && !viewport {
+                    active_line_style.on_top_of(selection_style)
+                } else {
+                    selection_style
+                };
+
+                let (_, start_y) = cursor.get_position();
+                // The line style takes precedence over the highlighting, but not over search
+                // matches.
+                let line_style_changes = ::std::iter::once((0, base_style))
+                    .chain(
+                        highlighting
+                            .get_info_for_line(line_index)
+                            .iter()
+                            .map(|&(pos, style)| (pos, base_style.on_top_of(style))),
+                    )
+                    .collect::<Vec<_>>();
+                let style_changes = overlay_style(
+                    &line_style_changes,
+                    &self.inner.search_matches(line_content),
+                    self.inner.search_match_style,
+                );
+                // The column cursor is drawn as a space after the end of empty lines.
+                let cursor_range = match self.inner.cursor_column {
+                    Some(column) if line_index == self.inner.current_line && !viewport => {
+                        Some(text::grapheme_at_column(line_content, column).0)
+                    }
+                    _ => None,
+                };
+                let style_changes = match cursor_range {
+                    Some(ref range) if !range.is_empty() => overlay_style(
+                        &style_changes,
+                        ::std::slice::from_ref(range),
+                        self.inner.cursor_style,
+                    ),
+                    _ => style_changes,
+                };
+                let (line_content, style_changes) = if truncate {
+                    let (cut_content, cut_changes) = text::cut_off_columns(
+                        line_content,
+                        &style_changes,
+                        self.inner.horizontal_offset,
+                    );
+                    (Cow::Owned(cut_content), cut_changes)
+                } else if line_content.contains('\t') {
+                    let (expanded_content, expanded_changes) =
+                        text::expand_tabs(line_content, &style_changes);
+                    (Cow::Owned(expanded_content), expanded_changes)
+                } else {
+                    (Cow::Borrowed(line_content), style_changes)
+                };
+                let mut last_change_pos = 0;
+                for &(change_pos, style) in style_changes.iter() {
+                    cursor.write(&line_content[last_change_pos..change_pos]);
+
+                    cursor.set_style_modifier(style);
+                    last_change_pos = change_pos;
+                }
+                cursor.write(&line_content[last_change_pos..]);
+                if let Some(true) = cursor_range.as_ref().map(Range::is_empty) {
+                    cursor.set_style_modifier(self.inner.cursor_style.on_top_of(base_style));
+                    cursor.write(" ");
+                }
+
+                if let Some(fold) = folds.fold_starting_at(line_index).filter(|f| f.closed) {
+                    let (line_end, _) = cursor.get_position();
+                    let available = width.saturating_sub(line_end.raw_value() as usize + 1);
+                    if available > 0 {
+                        let summary = format!("+-- {} lines", fold.num_lines());
+                        cursor.set_style_modifier(base_style);
+                        cursor.write(" ");
+                        cursor.set_style_modifier(
+                            self.inner.fold_summary_style.on_top_of(base_style),
+                        );
+                        cursor.write(text::truncate_to_width(&summary, available));
+                    }
+                }
+
+                let (line_end, _) = cursor.get_position();
+                for (column, text, style) in
+                    content
+                        .annotations
+                        .layout(line_index, line_end.raw_value() as usize, width)
+                {
+                    let (current_column, _) = cursor.get_position();
+                    cursor.set_style_modifier(base_style);
+                    cursor.write(&" ".repeat(column - current_column.raw_value() as usize));
+                    cursor.set_style_modifier(style.on_top_of(base_style));
+                    cursor.write(text);
+                }
+
+                cursor.set_style_modifier(base_style);
+                cursor.fill_and_wrap_line();
+                let (_, end_y) = cursor.get_position();
+                if start_y <= RowIndex::new(0) && end_y > RowIndex::new(0) {
+                    self.inner.viewport_top.set(line_index);
+                }
+
+                if truncate && self.inner.show_cut_off_indicators {
+                    if self.inner.horizontal_offset > 0 && !line.get_content().is_empty() {
+                        cursor.move_to(ColIndex::new(0), start_y);
+                        cursor.write("<");
+                    }
+                    if text::width(&line_content) > width {
+                        cursor.move_to(ColIndex::new(width as i32 - 1), start_y);
+                        cursor.write(">");
+                    }
+                    cursor.move_to(ColIndex::new(0), end_y);
+                }
+
+                if let Some(fold) = folds.fold_starting_at(line_index) {
+                    if RowIndex::new(0) <= start_y && start_y < height.from_origin() {
+                        let mut fold_cursor =
+                            Cursor::new(&mut fold_window).position(ColIndex::new(0), start_y);
+                        fold_cursor.write(if fold.closed { "+" } else { "-" });
+                    }
+                }
+
+                let range_start_y = min(max(start_y, RowIndex::new(0)), height.from_origin());
+                let range_end_y = min(max(end_y, RowIndex::new(0)), height.from_origin());
+                if range_start_y < range_end_y {
+                    layout.lines.push((line_index, start_y..end_y));
+                }
+                let wrap_info = WrapInfo {
+                    num_rows: max(0, (end_y - start_y).raw_value()) as usize,
+                    first_visible_row: max(0, (range_start_y - start_y).raw_value()) as usize,
+                };
+                content.decorator.decorate(
+                    line,
+                    line_index,
+                    self.inner.current_line,
+                    wrap_info,
+                    decoration_window.create_subwindow(.., range_start_y..range_end_y),
+                );
+                //decoration_window.create_subwindow(.., range_start_y..range_end_y).fill('X');
+            }
+            *self.inner.last_layout.borrow_mut() = layout;
+        } else {
+            *self.inner.last_layout.borrow_mut() = PagerLayout::default();
+        }
+    }
+}
+
+impl<L, D> Scrollable for Pager<L, D>
+where
+    L: PagerLine + ?Sized,
+    D: LineDecorator<Line = L>,
+{
+    fn scroll_backwards(&mut self) -> OperationResult {
+        self.scroll_backwards_by(1)
+    }
+    fn scroll_forwards(&mut self) -> OperationResult {
+        self.scroll_forwards_by(1)
+    }
+    fn scroll_to_beginning(&mut self) -> OperationResult {
+        if self.current_line == LineIndex::new(0) {
+            Err(())
+        } else {
+            self.jump_list.record(self.current_line.raw_value());
+            self.following = false;
+            self.current_line = LineIndex::new(0);
+            Ok(())
+        }
+    }
+    fn scroll_to_end(&mut self) -> OperationResult {
+        let previous_line = self.current_line.raw_value();
+        self.go_to_end()?;
+        self.jump_list.record(previous_line);
+        Ok(())
+    }
+}
+
+/// Map a line index from before to after replacing the lines in `range` by `num_new_lines` lines.
+///
+/// Indices of replaced lines are mapped to the first replacing line.
+fn adjust_index(index: usize, range: &Range<usize>, num_new_lines: usize) -> usize {
+    if index < range.start {
+        index
+    } else if index >= range.end {
+        index - (range.end - range.start) + num_new_lines
+    } else {
+        range.start
+    }
+}
+
+/// Byte ranges of the matches of `regex` in `text`.
+///
+/// Empty matches (e.g., of `x*` in a line without any `x`) cannot be highlighted and are therefore
+/// not considered to be search matches at all.
+fn non_empty_matches<'a>(
+    regex: &'a Regex,
+    text: &'a str,
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    regex
+        .find_iter(text)
+        .filter(|m| m.start() != m.end())
+        .map(|m| m.start()..m.end())
+}
+
+/// Combine the style changes of a line with an additional style that is applied on top of them
+/// within the given (byte) ranges of the line.
+fn overlay_style(
+    changes: &[(usize, StyleModifier)],
+    ranges: &[Range<usize>],
+    style: StyleModifier,
+) -> Vec<(usize, StyleModifier)> {
+    if ranges.is_empty() {
+        return changes.to_vec();
+    }
+    let mut positions = changes
+        .iter()
+        .map(|&(pos, _)| pos)
+        .chain(ranges.iter().flat_map(|r| vec![r.start, r.end]))
+        .collect::<Vec<_>>();
+    positions.sort();
+    positions.dedup();
+    positions
+        .into_iter()
+        .map(|pos| {
+            let base = changes
+                .iter()
+                .take_while(|&&(change_pos, _)| change_pos <= pos)
+                .last()
+                .map(|&(_, s)| s)
+                .unwrap_or_else(StyleModifier::new);
+            if ranges.iter().any(|r| r.start <= pos && pos < r.end) {
+                (pos, style.on_top_of(base))
+            } else {
+                (pos, base)
+            }
+        })
+        .collect()
+}
+
+/// Anything that represents a single line in a pager. Other than the main content (something
 /// string-like) it may also store additional information that can be used by a `Highlighter`.
 pub trait PagerLine {
     fn get_content(&self) -> &str;
@@ -391,42 +1943,135 @@ impl PagerLine for String {
     }
 }
 
+impl PagerLine for str {
+    fn get_content(&self) -> &str {
+        self
+    }
+}
+
 /// A collection of `PagerLines` including information about the highlighting state and (if
 /// present) a `LineDecorator`.
 ///
-/// Use `from_lines` or `from_file` to build an initial content and add highlighter and decorator
-/// using `with_highlighter` and `with_decorator`.
-pub struct PagerContent<L: PagerLine, D: LineDecorator> {
-    storage: Vec<L>,
-    highlight_info: HighlightInfo,
+/// Use `from_lines`, `from_file`, `from_file_mapped`, `from_ansi_file` or `from_source` to build
+/// an initial content and add highlighter and decorator using `with_highlighter` (or
+/// `with_lazy_highlighter`) and `with_decorator`. A name (e.g., the path of the displayed file) can
+/// be set using `with_name`.
+pub struct PagerContent<L: PagerLine + ?Sized, D: LineDecorator> {
+    storage: Box<dyn LineSource<Line = L>>,
+    name: Option<String>,
+    highlighting: RefCell<ContentHighlighting>,
     decorator: D,
+    annotations: Annotations,
+    folds: Folds,
+    cache: ContentCache,
+}
+
+/// Results of scanning the whole content, which are kept until the lines change.
+#[derive(Default)]
+struct ContentCache {
+    num_lines: Cell<Option<usize>>,
+    search_matches: RefCell<Option<SearchMatches>>,
 }
 
-impl<L: PagerLine> PagerContent<L, NoDecorator<L>> {
+/// The matches of a search pattern in the whole content.
+struct SearchMatches {
+    pattern: String,
+    lines: Vec<LineIndex>,
+    count: usize,
+}
+
+impl<L: PagerLine + 'static> PagerContent<L, NoDecorator<L>> {
     /// Create a simple `PagerContent` from a ordered collection of lines. The lines the `Vec` will
     /// be displayed top to bottom from beginning to end.
+    ///
+    /// As for `from_source`, the lines must not borrow anything (i.e., be `'static`), as the
+    /// content stores them as a `LineSource` trait object.
     pub fn from_lines(storage: Vec<L>) -> Self {
+        Self::from_source(storage)
+    }
+}
+
+impl<L: PagerLine + ?Sized> PagerContent<L, NoDecorator<L>> {
+    /// Create a `PagerContent` from any `LineSource`.
+    pub fn from_source<S: LineSource<Line = L> + 'static>(source: S) -> Self {
         PagerContent {
-            storage,
-            highlight_info: HighlightInfo::none(),
+            storage: Box::new(source),
+            name: None,
+            highlighting: RefCell::new(ContentHighlighting::None),
             decorator: NoDecorator::default(),
+            annotations: Annotations::default(),
+            folds: Folds::default(),
+            cache: ContentCache::default(),
         }
     }
 }
 
 impl PagerContent<String, NoDecorator<String>> {
     /// Try to load lines (as strings) from the given file as the lines of PagerContent.
+    ///
+    /// The path of the file is used as the name of the content.
     pub fn from_file<F: AsRef<::std::path::Path>>(file_path: F) -> ::std::io::Result<Self> {
         use std::io::Read;
+        let file_path = file_path.as_ref();
         let mut file = ::std::fs::File::open(file_path)?;
         let mut contents = String::new();
         file.read_to_string(&mut contents)?;
 
-        Ok(PagerContent {
-            storage: contents.lines().map(|s| s.to_owned()).collect::<Vec<_>>(),
-            highlight_info: HighlightInfo::none(),
-            decorator: NoDecorator::default(),
-        })
+        Ok(
+            Self::from_lines(contents.lines().map(|s| s.to_owned()).collect::<Vec<_>>())
+                .with_name(file_path.display().to_string()),
+        )
+    }
+
+    /// Create `PagerContent` from lines that contain ANSI escape sequences (e.g., the colored
+    /// output of a compiler).
+    ///
+    /// Escape sequences are removed from the lines and colors and text attributes set via SGR
+    /// sequences are used as the highlighting of the content. See `AnsiParser` for details.
+    ///
+    /// More lines with escape sequences can be added using `append_ansi_lines`, which continues
+    /// with the style at the end of the last line. As the escape sequences are not kept, lines can
+    /// otherwise only be removed from the end of the content (see `truncate`).
+    pub fn from_ansi_lines<S: AsRef<str>, I: IntoIterator<Item = S>>(lines: I) -> Self {
+        let mut ansi = AnsiHighlighting::new();
+        let storage = ansi.append_lines(lines);
+        let mut content = Self::from_lines(storage);
+        content.highlighting = RefCell::new(ContentHighlighting::Ansi(ansi));
+        content
+    }
+
+    /// Try to read a file that contains ANSI escape sequences (like the output of `less -R`)
+    /// and use its lines as the lines of PagerContent. See `from_ansi_lines`.
+    ///
+    /// The path of the file is used as the name of the content.
+    pub fn from_ansi_file<F: AsRef<::std::path::Path>>(file_path: F) -> ::std::io::Result<Self> {
+        use std::io::Read;
+        let file_path = file_path.as_ref();
+        let mut file = ::std::fs::File::open(file_path)?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        Ok(Self::from_ansi_lines(contents.lines()).with_name(file_path.display().to_string()))
+    }
+}
+
+impl PagerContent<str, NoDecorator<str>> {
+    /// Try to map the given file into memory and use its lines as the lines of PagerContent.
+    ///
+    /// In contrast to `from_file`, the file is not read up front, which makes this suitable for
+    /// very large files. See `MappedFile` for details.
+    ///
+    /// The path of the file is used as the name of the content.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified while the content exists (see `MappedFile::open`).
+    pub unsafe fn from_file_mapped<F: AsRef<::std::path::Path>>(
+        file_path: F,
+    ) -> ::std::io::Result<Self> {
+        let file_path = file_path.as_ref();
+        Ok(Self::from_source(MappedFile::open(file_path)?)
+            .with_name(file_path.display().to_string()))
     }
 }
 
@@ -436,40 +2081,233 @@ where
     D: LineDecorator<Line = L>,
 {
     /// Add a `Highlighter` to `PagerContent` that previously did not have one.
+    ///
+    /// All lines are highlighted immediately. For large contents, consider using
+    /// `with_lazy_highlighter` instead.
+    ///
+    /// As the highlighter is not kept, the highlighting cannot be updated when lines are added or
+    /// replaced, so only `truncate` is possible afterwards. In particular, `append_lines` (and
+    /// thus follow mode, see `Pager::set_follow_mode`) does not work for such content. Use
+    /// `with_eager_highlighter` or `with_lazy_highlighter` for content that changes.
     pub fn with_highlighter<HN: Highlighter>(self, highlighter: &HN) -> PagerContent<L, D> {
-        let highlight_info =
-            highlighter.highlight(self.storage.iter().map(|l| l as &dyn PagerLine));
+        let highlight_info = highlighter.highlight(
+            self.view(LineIndex::new(0)..)
+                .map(|(_, l)| l as &dyn PagerLine),
+        );
+        PagerContent {
+            storage: self.storage,
+            name: self.name,
+            highlighting: RefCell::new(ContentHighlighting::Fixed(
+                highlight_info,
+                highlighter.active_line_style(),
+            )),
+            decorator: self.decorator,
+            annotations: self.annotations,
+            folds: self.folds,
+            cache: self.cache,
+        }
+    }
+
+    /// Append lines to the end of the content.
+    ///
+    /// If the content is highlighted using `with_eager_highlighter` or `with_lazy_highlighter`,
+    /// only the new lines (and possibly a few preceding ones) will be highlighted, starting from
+    /// the last saved state of the highlighter.
+    ///
+    /// Content that is highlighted using `with_highlighter` cannot be extended, as the highlighter
+    /// is not kept to highlight the new lines. In this case, `PagerError::FixedHighlighting` is
+    /// returned and the content is left unchanged. The same applies to content created using
+    /// `from_ansi_lines`, which has to be extended using `append_ansi_lines` instead. An error is
+    /// also returned if the `LineSource` of the content cannot be modified.
+    pub fn append_lines<N: IntoIterator<Item = L>>(&mut self, lines: N) -> Result<(), PagerError> {
+        let len = self.storage.len();
+        self.splice(len..len, lines).map(|_| ())
+    }
+
+    /// Replace the lines in the specified range by the given lines.
+    ///
+    /// As for `view`, the range can be larger than what the `PagerContent` currently holds.
+    ///
+    /// If the content is highlighted using `with_eager_highlighter` or `with_lazy_highlighter`,
+    /// lines are re-highlighted starting at the first changed line until the state of the
+    /// highlighter converges.
+    ///
+    /// An error is returned if the `LineSource` of the content cannot be modified or if the
+    /// highlighting of the content cannot be updated (see `with_highlighter`).
+    pub fn replace_range<I, R, N>(&mut self, range: R, lines: N) -> Result<(), PagerError>
+    where
+        I: Into<LineIndex> + Clone,
+        R: RangeBounds<I>,
+        N: IntoIterator<Item = L>,
+    {
+        let range = self.resolve_range(range);
+        self.splice(range, lines).map(|_| ())
+    }
+
+    /// Remove all lines after the first `num_lines` lines.
+    ///
+    /// An error is returned if the `LineSource` of the content cannot be modified.
+    pub fn truncate(&mut self, num_lines: usize) -> Result<(), PagerError> {
+        self.replace_range(LineIndex::new(num_lines).., None)
+    }
+
+    /// Replace the stored lines in `range` by `lines` and return the number of new lines.
+    fn splice<N: IntoIterator<Item = L>>(
+        &mut self,
+        range: Range<usize>,
+        lines: N,
+    ) -> Result<usize, PagerError> {
+        let num_new_lines = {
+            let storage = self
+                .storage
+                .lines_mut()
+                .ok_or(PagerError::ImmutableContent)?;
+            let old_len = storage.len();
+            let mut lines = lines.into_iter().peekable();
+            let adds_lines = lines.peek().is_some();
+            if !self
+                .highlighting
+                .get_mut()
+                .can_replace(range.clone(), old_len, adds_lines)
+            {
+                return Err(PagerError::FixedHighlighting);
+            }
+            storage.splice(range.clone(), lines).for_each(drop);
+            storage.len() + (range.end - range.start) - old_len
+        };
+        self.highlighting
+            .get_mut()
+            .lines_replaced(range.clone(), num_new_lines);
+        self.lines_replaced(range, num_new_lines);
+        Ok(num_new_lines)
+    }
+
+    /// Update everything but the highlighting after the lines in `range` have been replaced by
+    /// `num_new_lines` new lines.
+    fn lines_replaced(&mut self, range: Range<usize>, num_new_lines: usize) {
+        self.annotations
+            .lines_replaced(range.clone(), num_new_lines);
+        self.folds.lines_replaced(range, num_new_lines);
+        self.cache = ContentCache::default();
+    }
+}
+
+impl<D> PagerContent<String, D>
+where
+    D: LineDecorator<Line = String>,
+{
+    /// Append lines that contain ANSI escape sequences to content created using
+    /// `from_ansi_lines` (or `from_ansi_file`).
+    ///
+    /// The lines are parsed as if they had been passed to `from_ansi_lines` along with the
+    /// existing lines, i.e., the style at the end of the last line is carried over to the new
+    /// lines.
+    ///
+    /// `PagerError::NotAnsiContent` is returned (and the content is left unchanged) if the
+    /// content was not created from lines with escape sequences or if its highlighting has been
+    /// replaced since.
+    pub fn append_ansi_lines<S: AsRef<str>, I: IntoIterator<Item = S>>(
+        &mut self,
+        lines: I,
+    ) -> Result<(), PagerError> {
+        let len = self.storage.len();
+        let num_new_lines = {
+            let storage = self
+                .storage
+                .lines_mut()
+                .ok_or(PagerError::ImmutableContent)?;
+            let ansi = match *self.highlighting.get_mut() {
+                ContentHighlighting::Ansi(ref mut ansi) => ansi,
+                _ => return Err(PagerError::NotAnsiContent),
+            };
+            let new_lines = ansi.append_lines(lines);
+            let num_new_lines = new_lines.len();
+            storage.extend(new_lines);
+            num_new_lines
+        };
+        self.lines_replaced(len..len, num_new_lines);
+        Ok(())
+    }
+}
+
+impl<L, D> PagerContent<L, D>
+where
+    L: PagerLine + ?Sized,
+    D: LineDecorator<Line = L>,
+{
+    /// Add an `IncrementalHighlighter` to `PagerContent` that previously did not have one.
+    ///
+    /// As for `with_highlighter`, all lines are highlighted immediately. The highlighter is kept,
+    /// however, so that lines that are added or replaced later are highlighted as well.
+    pub fn with_eager_highlighter<HN: IncrementalHighlighter + 'static>(
+        self,
+        highlighter: HN,
+    ) -> PagerContent<L, D> {
+        let highlighting = {
+            let get_line_content = |i| self.storage.line(i).map(|line| line.get_content());
+            ContentHighlighting::eager(highlighter, self.storage.len(), &get_line_content)
+        };
+        PagerContent {
+            storage: self.storage,
+            name: self.name,
+            highlighting: RefCell::new(highlighting),
+            decorator: self.decorator,
+            annotations: self.annotations,
+            folds: self.folds,
+            cache: self.cache,
+        }
+    }
+
+    /// Add an `IncrementalHighlighter` to `PagerContent` that previously did not have one.
+    ///
+    /// In contrast to `with_highlighter`, lines are only highlighted once they are about to be
+    /// displayed, so that large contents can be viewed without delay.
+    pub fn with_lazy_highlighter<HN: IncrementalHighlighter + 'static>(
+        self,
+        highlighter: HN,
+    ) -> PagerContent<L, D> {
         PagerContent {
             storage: self.storage,
-            highlight_info,
+            name: self.name,
+            highlighting: RefCell::new(ContentHighlighting::lazy(highlighter)),
             decorator: self.decorator,
+            annotations: self.annotations,
+            folds: self.folds,
+            cache: self.cache,
         }
     }
 }
 
 impl<L> PagerContent<L, NoDecorator<L>>
 where
-    L: PagerLine,
+    L: PagerLine + ?Sized,
 {
     /// Add a `Decorator` to `PagerContent` that previously did not have one.
     pub fn with_decorator<DN: LineDecorator<Line = L>>(self, decorator: DN) -> PagerContent<L, DN> {
         PagerContent {
             storage: self.storage,
-            highlight_info: self.highlight_info,
+            name: self.name,
+            highlighting: self.highlighting,
             decorator,
+            annotations: self.annotations,
+            folds: self.folds,
+            cache: self.cache,
         }
     }
 }
 
 impl<L, D> PagerContent<L, D>
 where
-    L: PagerLine,
+    L: PagerLine + ?Sized,
     D: LineDecorator<Line = L>,
 {
     /// Iterate over a specified range of lines stored.
     ///
     /// The specified range can be larger than what the `PagerContent` currently holds. In that
     /// case the additional indices are simply not part of the returned iterator.
+    ///
+    /// Note that if the end of the range is unbounded (or past the end of the content), lazy
+    /// `LineSource`s have to locate all of their lines.
     pub fn view<'a, I: Into<LineIndex> + Clone, R: RangeBounds<I>>(
         &'a self,
         range: R,
@@ -478,6 +2316,20 @@ where
         Self: ::std::marker::Sized,
     {
         // Not exactly sure, why this is needed... we only store a reference?!
+        let urange = self.resolve_range(range);
+        urange.map(move |i| {
+            (
+                LineIndex::new(i),
+                self.storage.line(i).expect("line in resolved range"),
+            )
+        })
+    }
+
+    /// Convert the given range to a range of indices of stored lines.
+    fn resolve_range<I: Into<LineIndex> + Clone, R: RangeBounds<I>>(
+        &self,
+        range: R,
+    ) -> Range<usize> {
         let start: LineIndex = match range.start_bound() {
             // Always inclusive
             Bound::Unbounded => LineIndex::new(0),
@@ -490,24 +2342,141 @@ where
             Bound::Included(i) => i.clone().into() + 1,
             Bound::Excluded(i) => i.clone().into(),
         };
-        let ustart = start.raw_value();
-        let uend = self.storage.len().min(end.raw_value());
-        let urange = ustart..uend;
-        urange
-            .clone()
-            .zip(self.storage[urange].iter())
-            .map(|(i, l)| (LineIndex::new(i), l))
+        // Avoid locating all lines of lazy sources if the end of the range is present.
+        let uend = match end.raw_value() {
+            0 => 0,
+            end if self.storage.line(end - 1).is_some() => end,
+            end => self.storage.len().min(end),
+        };
+        let ustart = uend.min(start.raw_value());
+        ustart..uend
     }
 
     /// Try to view a specific line with the given index.
     pub fn view_line<I: Into<LineIndex>>(&self, line: I) -> Option<&L> {
-        self.storage.get(line.into().raw_value())
+        self.storage.line(line.into().raw_value())
+    }
+
+    /// Get the number of lines stored.
+    ///
+    /// Note that lazy `LineSource`s may have to locate all of their lines to determine this. The
+    /// result is remembered until lines are added or replaced.
+    pub fn num_lines(&self) -> usize {
+        if let Some(num_lines) = self.cache.num_lines.get() {
+            return num_lines;
+        }
+        let num_lines = self.storage.len();
+        self.cache.num_lines.set(Some(num_lines));
+        num_lines
+    }
+
+    /// Get the matches of `regex` in all lines. The result is remembered until lines are added or
+    /// replaced or another pattern is searched for.
+    fn search_matches(&self, regex: &Regex) -> Ref<'_, SearchMatches> {
+        let up_to_date = match *self.cache.search_matches.borrow() {
+            Some(ref matches) => matches.pattern == regex.as_str(),
+            None => false,
+        };
+        if !up_to_date {
+            let mut matches = SearchMatches {
+                pattern: regex.as_str().to_owned(),
+                lines: Vec::new(),
+                count: 0,
+            };
+            for (index, line) in self.view(LineIndex::new(0)..) {
+                let count = non_empty_matches(regex, line.get_content()).count();
+                if count > 0 {
+                    matches.lines.push(index);
+                    matches.count += count;
+                }
+            }
+            *self.cache.search_matches.borrow_mut() = Some(matches);
+        }
+        Ref::map(self.cache.search_matches.borrow(), |matches| {
+            matches.as_ref().expect("search matches were just computed")
+        })
+    }
+
+    /// Get the number of lines stored, if it is known without further work (see
+    /// `LineSource::len_if_known`).
+    pub fn num_lines_if_known(&self) -> Option<usize> {
+        self.storage.len_if_known()
+    }
+
+    /// Set the name of the content, e.g., the path of the displayed file.
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Get the name of the content, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Set (or remove) the name of the content.
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
     }
 
     /// Overwrite the current decorator with a compatible one.
     pub fn set_decorator(&mut self, decorator: D) {
         self.decorator = decorator;
     }
+
+    /// Get the virtual text annotations of the lines.
+    pub fn annotations(&self) -> &Annotations {
+        &self.annotations
+    }
+
+    /// Get mutable access to the virtual text annotations of the lines, e.g., to display hints or
+    /// diagnostics next to lines without modifying them.
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        &mut self.annotations
+    }
+
+    fn visibility<'a>(&'a self, filter: Option<&'a LineFilter<L>>) -> Visibility<'a, L> {
+        Visibility {
+            storage: &*self.storage,
+            folds: &self.folds,
+            filter,
+        }
+    }
+
+    /// Get the folds of the content.
+    pub fn folds(&self) -> &Folds {
+        &self.folds
+    }
+
+    /// Get mutable access to the folds of the content, e.g., to define folds manually.
+    ///
+    /// Note that a `Pager` displaying the content does not move its active line out of folds that
+    /// are closed this way. Use `Pager::close_fold` and friends instead.
+    pub fn folds_mut(&mut self) -> &mut Folds {
+        &mut self.folds
+    }
+
+    /// Replace all folds of the content by the ones determined by the given `FoldProvider`.
+    ///
+    /// Note that this requires scanning the whole content.
+    pub fn compute_folds<P: FoldProvider>(&mut self, provider: &P) {
+        self.folds = Folds::from_provider(
+            provider,
+            self.view(LineIndex::new(0)..)
+                .map(|(_, line)| line.get_content()),
+        );
+    }
+
+    /// Get a reference to the current decorator.
+    pub fn decorator(&self) -> &D {
+        &self.decorator
+    }
+
+    /// Get a mutable reference to the current decorator, e.g., to update the information it
+    /// displays without reloading the content.
+    pub fn decorator_mut(&mut self) -> &mut D {
+        &mut self.decorator
+    }
 }
 
 /// All errors that can occur when operating on a `Pager` or its contents.
@@ -516,4 +2485,359 @@ pub enum PagerError {
     NoLineWithIndex(LineIndex),
     NoLineWithPredicate,
     NoContent,
+    NoSearchPattern,
+    NoMatch,
+    InvalidSearchPattern(regex::Error),
+    ImmutableContent,
+    NoFold,
+    FilteredOut(LineIndex),
+    NoMark(char),
+    NoJump,
+    FixedHighlighting,
+    NotAnsiContent,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Highlights lines that start with `#`.
+    struct CommentHighlighter;
+
+    impl IncrementalHighlighter for CommentHighlighter {
+        type State = ();
+
+        fn initial_state(&self) {}
+
+        fn highlight_lines(&self, _: &mut (), lines: &[&str]) -> Vec<Vec<(usize, StyleModifier)>> {
+            lines
+                .iter()
+                .map(|line| {
+                    if line.starts_with('#') {
+                        vec![(0, StyleModifier::new().bold(true))]
+                    } else {
+                        Vec::new()
+                    }
+                })
+                .collect()
+        }
+
+        fn default_style(&self) -> StyleModifier {
+            StyleModifier::new()
+        }
+    }
+
+    impl Highlighter for CommentHighlighter {
+        fn highlight<'a, I: Iterator<Item = &'a dyn PagerLine>>(&self, lines: I) -> HighlightInfo {
+            let lines = lines.map(|line| line.get_content()).collect::<Vec<_>>();
+            HighlightInfo {
+                style_changes: self.highlight_lines(&mut (), &lines),
+                default_style: StyleModifier::new(),
+            }
+        }
+    }
+
+    fn lines(num_lines: usize) -> Vec<String> {
+        (0..num_lines).map(|i| format!("line {}", i)).collect()
+    }
+
+    /// A pager with 20 lines, where line 12 is active and lines 10 to 12 are selected.
+    ///
+    /// Mark `a` is at line 10, mark `b` at line 12, the jump list contains lines 0 and 10, and
+    /// lines 4 and 13 are highlighted.
+    fn pager() -> Pager<String> {
+        let mut lines = lines(20);
+        lines[4] = "# 4".to_owned();
+        lines[13] = "# 13".to_owned();
+        let mut pager = Pager::new();
+        pager.load(PagerContent::from_lines(lines).with_lazy_highlighter(CommentHighlighter));
+        pager.go_to_line(LineIndex::new(10)).unwrap();
+        pager.set_mark('a');
+        pager.start_selection();
+        pager.go_to_line(LineIndex::new(12)).unwrap();
+        pager.set_mark('b');
+        pager
+    }
+
+    fn line(pager: &Pager<String>) -> usize {
+        pager.current_line_index().raw_value()
+    }
+
+    fn marks(pager: &Pager<String>) -> Vec<(char, usize)> {
+        pager
+            .marks()
+            .map(|(name, line)| (name, line.raw_value()))
+            .collect()
+    }
+
+    fn selection(pager: &Pager<String>) -> Option<Range<usize>> {
+        pager
+            .selection()
+            .map(|range| range.start.raw_value()..range.end.raw_value())
+    }
+
+    fn folds(pager: &Pager<String>) -> Vec<(usize, usize)> {
+        pager
+            .content()
+            .unwrap()
+            .folds()
+            .iter()
+            .map(|fold| (fold.first.raw_value(), fold.last.raw_value()))
+            .collect()
+    }
+
+    fn add_folds(pager: &mut Pager<String>, folds: &[(usize, usize)]) {
+        let content = pager.content_mut().unwrap();
+        for &(first, last) in folds {
+            content
+                .folds_mut()
+                .add(LineIndex::new(first), LineIndex::new(last));
+        }
+    }
+
+    /// Lines (from the first `num_lines`) that are highlighted.
+    fn highlighted_lines(pager: &Pager<String>, num_lines: usize) -> Vec<usize> {
+        let content = pager.content().unwrap();
+        let mut highlighting = content.highlighting.borrow_mut();
+        highlighting.prepare(0..num_lines, &|i| {
+            content
+                .view_line(LineIndex::new(i))
+                .map(|line| line.get_content())
+        });
+        (0..num_lines)
+            .filter(|&i| !highlighting.get_info_for_line(LineIndex::new(i)).is_empty())
+            .collect()
+    }
+
+    /// Follow the jump list back to its beginning.
+    fn jumps_back(pager: &mut Pager<String>) -> Vec<usize> {
+        let mut jumps = Vec::new();
+        while pager.jump_back().is_ok() {
+            jumps.push(line(pager));
+        }
+        jumps
+    }
+
+    #[test]
+    fn replace_before_active_line() {
+        let mut pager = pager();
+        add_folds(&mut pager, &[(1, 3), (6, 8)]);
+        pager
+            .replace_range(LineIndex::new(2)..LineIndex::new(5), vec!["x".to_owned()])
+            .unwrap();
+        assert_eq!(line(&pager), 10);
+        assert_eq!(marks(&pager), vec![('a', 8), ('b', 10)]);
+        assert_eq!(selection(&pager), Some(8..11));
+        // The fold overlapping the start of the replaced lines is removed.
+        assert_eq!(folds(&pager), vec![(4, 6)]);
+        assert_eq!(highlighted_lines(&pager, 18), vec![11]);
+        assert_eq!(jumps_back(&mut pager), vec![8, 0]);
+    }
+
+    #[test]
+    fn replace_overlapping_active_line() {
+        let mut pager = pager();
+        add_folds(&mut pager, &[(8, 15), (11, 13)]);
+        pager
+            .replace_range(
+                LineIndex::new(11)..LineIndex::new(14),
+                vec!["# y".to_owned()],
+            )
+            .unwrap();
+        // The first replacing line takes the place of the replaced active line.
+        assert_eq!(line(&pager), 11);
+        assert_eq!(marks(&pager), vec![('a', 10), ('b', 11)]);
+        assert_eq!(selection(&pager), Some(10..12));
+        // The enclosing fold shrinks, while the replaced fold is removed.
+        assert_eq!(folds(&pager), vec![(8, 13)]);
+        assert_eq!(highlighted_lines(&pager, 18), vec![4, 11]);
+        assert_eq!(jumps_back(&mut pager), vec![10, 0]);
+    }
+
+    #[test]
+    fn replace_after_active_line() {
+        let mut pager = pager();
+        add_folds(&mut pager, &[(10, 14), (15, 17)]);
+        pager
+            .replace_range(
+                LineIndex::new(13)..LineIndex::new(14),
+                vec!["a".to_owned(), "# b".to_owned()],
+            )
+            .unwrap();
+        assert_eq!(line(&pager), 12);
+        assert_eq!(marks(&pager), vec![('a', 10), ('b', 12)]);
+        assert_eq!(selection(&pager), Some(10..13));
+        assert_eq!(folds(&pager), vec![(10, 15), (16, 18)]);
+        assert_eq!(highlighted_lines(&pager, 21), vec![4, 14]);
+        assert_eq!(jumps_back(&mut pager), vec![10, 0]);
+    }
+
+    #[test]
+    fn truncate_before_active_line() {
+        let mut pager = pager();
+        pager.truncate(11).unwrap();
+        // Positions after the end move to the new last line, unless they refer to removed lines.
+        assert_eq!(line(&pager), 10);
+        assert_eq!(marks(&pager), vec![('a', 10)]);
+        assert_eq!(selection(&pager), Some(10..11));
+        assert_eq!(highlighted_lines(&pager, 11), vec![4]);
+        assert_eq!(jumps_back(&mut pager), vec![0]);
+    }
+
+    #[test]
+    fn fixed_highlighting_refuses_new_lines() {
+        let mut pager = Pager::new();
+        pager.load(PagerContent::from_lines(lines(20)).with_highlighter(&CommentHighlighter));
+        pager.go_to_line(LineIndex::new(12)).unwrap();
+        for result in [
+            pager.append_lines(vec!["x".to_owned()]),
+            pager.replace_range(LineIndex::new(2)..LineIndex::new(3), vec!["x".to_owned()]),
+            pager.replace_range(LineIndex::new(18)..LineIndex::new(20), vec!["x".to_owned()]),
+        ] {
+            match result {
+                Err(PagerError::FixedHighlighting) => {}
+                other => panic!("unexpected result {:?}", other),
+            }
+        }
+        assert_eq!(pager.content().unwrap().num_lines(), 20);
+        assert_eq!(line(&pager), 12);
+
+        pager.truncate(5).unwrap();
+        assert_eq!(pager.content().unwrap().num_lines(), 5);
+        assert_eq!(line(&pager), 4);
+    }
+
+    #[test]
+    fn annotations_do_not_add_rows() {
+        use unsegen::base::terminal::test::FakeTerminal;
+
+        let mut content = PagerContent::from_lines(vec![
+            "abcdefgh".to_owned(),
+            "0123456789".to_owned(),
+            "abc".to_owned(),
+        ]);
+        for (line, text) in [(0, "note"), (1, "long note"), (2, "note")] {
+            content.annotations_mut().add(
+                LineIndex::new(line),
+                Annotation::end_of_line(text, StyleModifier::new()),
+            );
+        }
+        let mut pager = Pager::new();
+        pager.load(content);
+        pager.set_scroll_mode(ScrollMode::Viewport);
+        pager.set_line_wrapping(LineWrapping::Wrap);
+
+        let mut terminal = FakeTerminal::with_size((8, 5));
+        pager
+            .as_widget()
+            .draw(terminal.create_root_window(), RenderingHints::default());
+        terminal.assert_looks_like("abcdefgh|01234567|89 long |abc note|        ");
+        let rows = pager
+            .layout()
+            .lines
+            .iter()
+            .map(|&(line, ref rows)| {
+                (
+                    line.raw_value(),
+                    rows.start.raw_value()..rows.end.raw_value(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![(0, 0..1), (1, 1..3), (2, 3..4)]);
+    }
+
+    #[test]
+    fn changing_lines_invalidates_cache() {
+        let mut pager = Pager::new();
+        pager.load(PagerContent::from_lines(lines(12)));
+        pager.set_search_pattern("line 1").unwrap();
+        let match_lines = |pager: &Pager<String>| {
+            pager
+                .search_match_lines()
+                .into_iter()
+                .map(LineIndex::raw_value)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pager.content().unwrap().num_lines(), 12);
+        assert_eq!(pager.search_match_count(), 3);
+        assert_eq!(match_lines(&pager), vec![1, 10, 11]);
+
+        pager
+            .append_lines(vec!["line 1".to_owned(), "line x".to_owned()])
+            .unwrap();
+        assert_eq!(pager.content().unwrap().num_lines(), 14);
+        assert_eq!(pager.search_match_count(), 4);
+        assert_eq!(match_lines(&pager), vec![1, 10, 11, 12]);
+
+        pager
+            .replace_range(
+                LineIndex::new(0)..LineIndex::new(2),
+                vec!["line 1 line 1".to_owned()],
+            )
+            .unwrap();
+        assert_eq!(pager.content().unwrap().num_lines(), 13);
+        assert_eq!(pager.search_match_count(), 5);
+        assert_eq!(match_lines(&pager), vec![0, 9, 10, 11]);
+
+        pager.truncate(10).unwrap();
+        assert_eq!(pager.content().unwrap().num_lines(), 10);
+        assert_eq!(pager.search_match_count(), 3);
+        assert_eq!(match_lines(&pager), vec![0, 9]);
+
+        pager.load(PagerContent::from_ansi_lines(vec!["line 1"]));
+        assert_eq!(pager.search_match_count(), 1);
+        pager
+            .append_ansi_lines(vec!["\x1b[31mline 1", "line 2"])
+            .unwrap();
+        assert_eq!(pager.content().unwrap().num_lines(), 3);
+        assert_eq!(pager.search_match_count(), 2);
+    }
+
+    #[test]
+    fn append_ansi_lines() {
+        let red = StyleModifier::new().fg_color(Color::Red);
+        let bold_red = red.bold(true);
+        let style_changes = |pager: &Pager<String>, line: usize| {
+            pager
+                .content()
+                .unwrap()
+                .highlighting
+                .borrow()
+                .get_info_for_line(LineIndex::new(line))
+                .to_vec()
+        };
+
+        let mut pager = Pager::new();
+        pager.load(PagerContent::from_ansi_lines(vec!["a", "\x1b[31mb"]));
+        pager.set_follow_mode(true);
+        pager
+            .append_ansi_lines(vec!["c\x1b[1m", "d\x1b[0m", "e"])
+            .unwrap();
+        assert_eq!(pager.content().unwrap().num_lines(), 5);
+        assert_eq!(line(&pager), 4);
+        assert_eq!(pager.current_line().unwrap(), "e");
+        assert_eq!(style_changes(&pager, 2), vec![(0, red), (1, bold_red)]);
+        assert_eq!(
+            style_changes(&pager, 3),
+            vec![(0, bold_red), (1, StyleModifier::new())]
+        );
+        assert_eq!(style_changes(&pager, 4), vec![]);
+
+        // The style at the end of the remaining lines is used after truncating.
+        pager.truncate(3).unwrap();
+        pager.append_ansi_lines(vec!["f"]).unwrap();
+        assert_eq!(pager.current_line().unwrap(), "f");
+        assert_eq!(style_changes(&pager, 3), vec![(0, bold_red)]);
+
+        match pager.append_lines(vec!["\x1b[0mg".to_owned()]) {
+            Err(PagerError::FixedHighlighting) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        pager.load(PagerContent::from_lines(lines(2)));
+        match pager.append_ansi_lines(vec!["x"]) {
+            Err(PagerError::NotAnsiContent) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(pager.content().unwrap().num_lines(), 2);
+    }
 }
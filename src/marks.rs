@@ -0,0 +1,191 @@
+/// Remembering positions in the content of a `Pager`: Named marks and the jump list.
+use std::collections::BTreeMap;
+
+/// Lines that were active before large jumps (e.g., to a search match), in the order in which
+/// they were visited.
+#[derive(Clone, Debug, Default)]
+pub struct JumpList {
+    entries: Vec<usize>,
+    /// Index of the entry that corresponds to the active line, or `entries.len()` if the active
+    /// line was reached by a new jump.
+    position: usize,
+}
+
+impl JumpList {
+    /// Remember `line` as the line that was active before a jump.
+    ///
+    /// Any entries that were visited by going back in the list are forgotten.
+    pub fn record(&mut self, line: usize) {
+        self.entries.truncate(self.position);
+        if self.entries.last() != Some(&line) {
+            self.entries.push(line);
+        }
+        self.position = self.entries.len();
+    }
+
+    /// Get the line to go to when going back from `current_line`, if any.
+    pub fn back(&mut self, current_line: usize) -> Option<usize> {
+        if self.position == self.entries.len() {
+            // Remember where we came from, so that we can go forward again.
+            if self.entries.last() != Some(&current_line) {
+                self.entries.push(current_line);
+            }
+            self.position = self.entries.len() - 1;
+        }
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        Some(self.entries[self.position])
+    }
+
+    /// Get the line to go to when going forward again after going back, if any.
+    pub fn forward(&mut self) -> Option<usize> {
+        if self.position + 1 >= self.entries.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(self.entries[self.position])
+    }
+
+    /// Forget all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.position = 0;
+    }
+
+    /// Update all entries using `update`, forgetting those for which it returns `None`.
+    pub fn update<F: FnMut(usize) -> Option<usize>>(&mut self, mut update: F) {
+        let mut new_position = None;
+        let mut entries = Vec::with_capacity(self.entries.len());
+        for (i, &line) in self.entries.iter().enumerate() {
+            let line = update(line);
+            if i == self.position {
+                // The entry may be merged with the previous one or be forgotten, in which case the
+                // position moves to the previous or next entry, respectively.
+                new_position = Some(match line {
+                    Some(line) if entries.last() == Some(&line) => entries.len() - 1,
+                    _ => entries.len(),
+                });
+            }
+            if let Some(line) = line {
+                if entries.last() != Some(&line) {
+                    entries.push(line);
+                }
+            }
+        }
+        self.position = new_position.unwrap_or(entries.len());
+        self.entries = entries;
+    }
+}
+
+/// Update all marks using `update`, removing those for which it returns `None`.
+pub fn update_marks<F: FnMut(usize) -> Option<usize>>(
+    marks: &mut BTreeMap<char, usize>,
+    mut update: F,
+) {
+    let old_marks = ::std::mem::take(marks);
+    marks.extend(
+        old_marks
+            .into_iter()
+            .filter_map(|(name, line)| update(line).map(|line| (name, line))),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn back_and_forward() {
+        let mut jumps = JumpList::default();
+        assert_eq!(jumps.back(5), None);
+        jumps.record(10);
+        jumps.record(20);
+        assert_eq!(jumps.back(30), Some(20));
+        assert_eq!(jumps.back(20), Some(10));
+        assert_eq!(jumps.back(10), None);
+        assert_eq!(jumps.forward(), Some(20));
+        assert_eq!(jumps.forward(), Some(30));
+        assert_eq!(jumps.forward(), None);
+    }
+
+    #[test]
+    fn record_ignores_repetitions() {
+        let mut jumps = JumpList::default();
+        jumps.record(10);
+        jumps.record(10);
+        assert_eq!(jumps.back(20), Some(10));
+        assert_eq!(jumps.back(10), None);
+    }
+
+    #[test]
+    fn record_after_back_forgets_later_entries() {
+        let mut jumps = JumpList::default();
+        jumps.record(10);
+        jumps.record(20);
+        assert_eq!(jumps.back(30), Some(20));
+        jumps.record(20);
+        assert_eq!(jumps.forward(), None);
+        assert_eq!(jumps.back(40), Some(20));
+        assert_eq!(jumps.back(20), Some(10));
+        assert_eq!(jumps.forward(), Some(20));
+        assert_eq!(jumps.forward(), Some(40));
+        assert_eq!(jumps.forward(), None);
+    }
+
+    #[test]
+    fn update_moves_and_forgets_entries() {
+        let mut jumps = JumpList::default();
+        jumps.record(10);
+        jumps.record(20);
+        jumps.record(30);
+        assert_eq!(jumps.back(40), Some(30));
+        assert_eq!(jumps.back(30), Some(20));
+
+        // The active entry is forgotten, so the position moves to the next entry.
+        jumps.update(|line| if line == 20 { None } else { Some(line + 1) });
+        assert_eq!(jumps.forward(), Some(41));
+        assert_eq!(jumps.back(41), Some(31));
+        assert_eq!(jumps.back(31), Some(11));
+        assert_eq!(jumps.back(11), None);
+    }
+
+    #[test]
+    fn update_merges_entries() {
+        let mut jumps = JumpList::default();
+        jumps.record(10);
+        jumps.record(20);
+        jumps.record(30);
+        assert_eq!(jumps.back(40), Some(30));
+        jumps.update(|line| Some(if line < 35 { 5 } else { line }));
+        assert_eq!(jumps.forward(), Some(40));
+        assert_eq!(jumps.back(40), Some(5));
+        assert_eq!(jumps.back(5), None);
+    }
+
+    #[test]
+    fn clear_forgets_everything() {
+        let mut jumps = JumpList::default();
+        jumps.record(10);
+        jumps.clear();
+        assert_eq!(jumps.back(20), None);
+        assert_eq!(jumps.forward(), None);
+    }
+
+    #[test]
+    fn update_marks_moves_and_removes_marks() {
+        let mut marks = BTreeMap::new();
+        marks.insert('a', 1);
+        marks.insert('b', 5);
+        marks.insert('c', 8);
+        update_marks(
+            &mut marks,
+            |line| if line < 3 { None } else { Some(line + 2) },
+        );
+        assert_eq!(
+            marks.into_iter().collect::<Vec<_>>(),
+            vec![('b', 7), ('c', 10)]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+/// A vertical scrollbar that indicates which part of the content of a `Pager` is visible.
+use unsegen::base::basic_types::*;
+use unsegen::base::{Cursor, StyleModifier, Window};
+use unsegen::widget::{Demand, Demand2D, RenderingHints, Widget};
+
+use super::{LineDecorator, Pager, PagerLine, SignDecorator};
+
+use std::cmp::{max, min};
+
+/// A widget that displays the position and size of the visible part of the content of a `Pager`
+/// relative to the whole content, e.g., to the right of the pager.
+///
+/// The scrollbar is one column wide and is meant to be drawn with the same height as the pager.
+/// Optionally, lines of interest (search matches, marks, signs or arbitrary lines) are marked on
+/// the scrollbar. Use `Pager::handle_scrollbar_mouse_event` to support clicking and dragging.
+///
+/// Note that the total number of lines of the content is required, so lazy `LineSource`s have to
+/// locate all of their lines.
+pub struct Scrollbar<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    pager: &'a Pager<L, D>,
+    track_style: StyleModifier,
+    thumb_style: StyleModifier,
+    search_match_style: Option<StyleModifier>,
+    mark_style: Option<StyleModifier>,
+    markers: Vec<(LineIndex, StyleModifier)>,
+}
+
+impl<'a, L, D> Scrollbar<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    /// Create a scrollbar for the given pager without any markers.
+    pub fn new(pager: &'a Pager<L, D>) -> Self {
+        Scrollbar {
+            pager,
+            track_style: StyleModifier::new(),
+            thumb_style: StyleModifier::new().invert(true),
+            search_match_style: None,
+            mark_style: None,
+            markers: Vec::new(),
+        }
+    }
+
+    /// Set the style of the part of the scrollbar that does not correspond to visible lines.
+    pub fn with_track_style(mut self, style: StyleModifier) -> Self {
+        self.track_style = style;
+        self
+    }
+
+    /// Set the style of the part of the scrollbar that corresponds to the visible lines.
+    pub fn with_thumb_style(mut self, style: StyleModifier) -> Self {
+        self.thumb_style = style;
+        self
+    }
+
+    /// Mark all lines that contain a match of the current search pattern using the given style.
+    ///
+    /// Note that this requires scanning the whole content whenever the content or the search
+    /// pattern changes.
+    pub fn with_search_markers(mut self, style: StyleModifier) -> Self {
+        self.search_match_style = Some(style);
+        self
+    }
+
+    /// Mark all lines that have a mark (see `Pager::set_mark`) using the given style.
+    pub fn with_mark_markers(mut self, style: StyleModifier) -> Self {
+        self.mark_style = Some(style);
+        self
+    }
+
+    /// Mark the given lines using the given style.
+    pub fn with_markers<I: IntoIterator<Item = LineIndex>>(
+        mut self,
+        lines: I,
+        style: StyleModifier,
+    ) -> Self {
+        self.markers
+            .extend(lines.into_iter().map(|line| (line, style)));
+        self
+    }
+
+    /// Mark all lines that have a sign using the style of the sign.
+    pub fn with_sign_markers(mut self, signs: &SignDecorator<L>) -> Self {
+        self.markers
+            .extend(signs.signs().map(|(line, sign)| (line, sign.style)));
+        self
+    }
+}
+
+impl<'a, L, D> Widget for Scrollbar<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    fn space_demand(&self) -> Demand2D {
+        Demand2D {
+            width: Demand::exact(1),
+            height: Demand::at_least(1),
+        }
+    }
+    fn draw(&self, mut window: Window, _: RenderingHints) {
+        let height: usize = window.get_height().into();
+        if height == 0 {
+            return;
+        }
+        let num_lines = self.pager.content().map(|c| c.num_lines()).unwrap_or(0);
+        let row_of_line = |line: LineIndex| {
+            (line.raw_value() * height)
+                .checked_div(num_lines)
+                .map_or(0, |row| min(row, height.saturating_sub(1)))
+        };
+
+        let layout = self.pager.layout();
+        let current_line = self.pager.current_line_index();
+        let first_line = layout.first_visible_line().unwrap_or(current_line);
+        let last_line = layout.last_visible_line().unwrap_or(current_line);
+        let thumb = match ((last_line.raw_value() + 1) * height).checked_div(num_lines) {
+            Some(end) => {
+                let start = row_of_line(first_line);
+                start..max(min(end, height), start + 1)
+            }
+            None => 0..height,
+        };
+
+        let mut row_styles = (0..height)
+            .map(|row| {
+                if thumb.start <= row && row < thumb.end {
+                    (" ", self.thumb_style)
+                } else {
+                    ("│", self.track_style)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut markers = Vec::new();
+        if let Some(style) = self.search_match_style {
+            markers.extend(
+                self.pager
+                    .search_match_lines()
+                    .into_iter()
+                    .map(|line| (line, style)),
+            );
+        }
+        if let Some(style) = self.mark_style {
+            markers.extend(self.pager.marks().map(|(_, line)| (line, style)));
+        }
+        markers.extend(self.markers.iter().cloned());
+        if num_lines > 0 {
+            for (line, style) in markers {
+                if line.raw_value() < num_lines {
+                    let row_style = &mut row_styles[row_of_line(line)];
+                    *row_style = ("-", style.on_top_of(row_style.1));
+                }
+            }
+        }
+
+        let mut cursor = Cursor::new(&mut window);
+        for (row, (glyph, style)) in row_styles.into_iter().enumerate() {
+            cursor.move_to(ColIndex::new(0), RowIndex::new(row as i32));
+            cursor.set_style_modifier(style);
+            cursor.write(glyph);
+        }
+    }
+}
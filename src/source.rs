@@ -0,0 +1,305 @@
+/// Types related to the storage of lines displayed in a `Pager`.
+///
+/// `LineSource` defines the interface for any line storage. `Vec`s of lines are the most simple
+/// implementation, `MappedFile` allows viewing (large) files without reading them into memory.
+use memmap2::Mmap;
+
+use super::PagerLine;
+
+use std::cell::{Cell, OnceCell, RefCell};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Interface for anything that stores the lines of `PagerContent`.
+///
+/// Lines are accessed by their index. Implementations may locate lines lazily, in which case the
+/// total number of lines is not known until the end of the source has been reached.
+pub trait LineSource {
+    /// The type of lines stored in the source.
+    type Line: PagerLine + ?Sized;
+
+    /// Get the line with the given index, or `None` if the source holds fewer lines.
+    fn line(&self, index: usize) -> Option<&Self::Line>;
+
+    /// Get the total number of lines, if it is known without further work.
+    fn len_if_known(&self) -> Option<usize>;
+
+    /// Get the total number of lines.
+    ///
+    /// Lazy implementations may have to scan the whole source to determine it.
+    fn len(&self) -> usize;
+
+    /// Check whether the source does not hold any line.
+    fn is_empty(&self) -> bool {
+        self.line(0).is_none()
+    }
+
+    /// Get mutable access to the stored lines, if the source can be modified.
+    fn lines_mut(&mut self) -> Option<&mut Vec<Self::Line>>
+    where
+        Self::Line: Sized,
+    {
+        None
+    }
+}
+
+impl<L: PagerLine> LineSource for Vec<L> {
+    type Line = L;
+
+    fn line(&self, index: usize) -> Option<&L> {
+        self.get(index)
+    }
+
+    fn len_if_known(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn lines_mut(&mut self) -> Option<&mut Vec<L>> {
+        Some(self)
+    }
+}
+
+/// Number of lines per chunk of `MappedFile::converted_lines`.
+const CONVERTED_CHUNK_SIZE: usize = 1 << 16;
+
+/// Converted lines of a `MappedFile`, if any, for `CONVERTED_CHUNK_SIZE` consecutive lines.
+type ConvertedChunk = Box<[OnceCell<Box<str>>]>;
+
+/// A memory-mapped file whose lines are located on demand.
+///
+/// Opening a `MappedFile` is cheap regardless of the file size, only the parts of the file that
+/// are actually viewed have to be read. Lines are separated by `\n` (or `\r\n`). Lines that are
+/// not valid UTF-8 are converted as by `String::from_utf8_lossy` when they are first accessed.
+pub struct MappedFile {
+    map: Option<Mmap>,
+    /// Byte offsets of the beginnings of all lines that have been located so far.
+    line_starts: RefCell<Vec<usize>>,
+    /// Whether all lines of the file have been located.
+    complete: Cell<bool>,
+    /// Converted lines that are not valid UTF-8, in chunks of `CONVERTED_CHUNK_SIZE` lines. Chunks
+    /// are allocated once they are needed and never moved, so that converted lines can be
+    /// borrowed for as long as the file.
+    converted_lines: Box<[OnceCell<ConvertedChunk>]>,
+}
+
+impl MappedFile {
+    /// Map the file at the given path into memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified (e.g., truncated or rewritten by another process) while it is
+    /// mapped, which results in undefined behavior or the process being killed (by `SIGBUS`). For
+    /// files that may change, read the file into memory instead (e.g., using
+    /// `PagerContent::from_file`).
+    pub unsafe fn open<F: AsRef<Path>>(file_path: F) -> io::Result<Self> {
+        let file = File::open(file_path)?;
+        // Empty files cannot be mapped.
+        let map = if file.metadata()?.len() > 0 {
+            Some(Mmap::map(&file)?)
+        } else {
+            None
+        };
+        let empty = map.is_none();
+        // Every line but the last one occupies at least one byte.
+        let max_lines = map.as_ref().map(|map| map.len() + 1).unwrap_or(0);
+        Ok(MappedFile {
+            map,
+            line_starts: RefCell::new(if empty { Vec::new() } else { vec![0] }),
+            complete: Cell::new(empty),
+            converted_lines: (0..max_lines.div_ceil(CONVERTED_CHUNK_SIZE))
+                .map(|_| OnceCell::new())
+                .collect(),
+        })
+    }
+
+    fn data(&self) -> &[u8] {
+        self.map.as_ref().map(|map| &map[..]).unwrap_or(&[])
+    }
+
+    /// Locate lines until either the beginning of the line after `index` or the end of the file
+    /// is found.
+    fn locate_lines_until(&self, index: usize) {
+        let data = self.data();
+        let mut line_starts = self.line_starts.borrow_mut();
+        while !self.complete.get() && line_starts.len() <= index.saturating_add(1) {
+            let last_start = *line_starts.last().expect("non-empty file has a line");
+            match data[last_start..].iter().position(|&b| b == b'\n') {
+                Some(pos) if last_start + pos + 1 < data.len() => {
+                    line_starts.push(last_start + pos + 1)
+                }
+                _ => self.complete.set(true),
+            }
+        }
+    }
+}
+
+impl LineSource for MappedFile {
+    type Line = str;
+
+    fn line(&self, index: usize) -> Option<&str> {
+        self.locate_lines_until(index);
+        let data = self.data();
+        let (start, next_start) = {
+            let line_starts = self.line_starts.borrow();
+            (
+                *line_starts.get(index)?,
+                line_starts.get(index + 1).cloned(),
+            )
+        };
+        let mut end = next_start.unwrap_or(data.len());
+        if end > start && data[end - 1] == b'\n' {
+            end -= 1;
+        }
+        if end > start && data[end - 1] == b'\r' {
+            end -= 1;
+        }
+        let bytes = &data[start..end];
+        Some(match ::std::str::from_utf8(bytes) {
+            Ok(line) => line,
+            Err(_) => {
+                let chunk = self.converted_lines[index / CONVERTED_CHUNK_SIZE]
+                    .get_or_init(|| (0..CONVERTED_CHUNK_SIZE).map(|_| OnceCell::new()).collect());
+                chunk[index % CONVERTED_CHUNK_SIZE]
+                    .get_or_init(|| String::from_utf8_lossy(bytes).into_owned().into_boxed_str())
+            }
+        })
+    }
+
+    fn len_if_known(&self) -> Option<usize> {
+        if self.complete.get() {
+            Some(self.line_starts.borrow().len())
+        } else {
+            None
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.locate_lines_until(usize::MAX);
+        self.line_starts.borrow().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A file in the temporary directory that is removed once it is dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, data: &[u8]) -> Self {
+            let path = ::std::env::temp_dir().join(format!(
+                "unsegen_pager_{}_{}",
+                ::std::process::id(),
+                name
+            ));
+            fs::write(&path, data).unwrap();
+            TempFile(path)
+        }
+
+        fn map(&self) -> MappedFile {
+            unsafe { MappedFile::open(&self.0).unwrap() }
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn all_lines(file: &MappedFile) -> Vec<&str> {
+        (0..).map_while(|i| file.line(i)).collect()
+    }
+
+    #[test]
+    fn empty_file() {
+        let tmp = TempFile::new("empty", b"");
+        let file = tmp.map();
+        assert_eq!(file.len_if_known(), Some(0));
+        assert!(file.is_empty());
+        assert_eq!(file.line(0), None);
+    }
+
+    #[test]
+    fn line_endings() {
+        let tmp = TempFile::new("line_endings", b"a\r\nb\n\r\n\nc\r\n");
+        let file = tmp.map();
+        assert_eq!(file.len_if_known(), None);
+        assert_eq!(file.line(1), Some("b"));
+        assert_eq!(all_lines(&file), vec!["a", "b", "", "", "c"]);
+        assert_eq!(file.len_if_known(), Some(5));
+    }
+
+    #[test]
+    fn missing_final_newline() {
+        let tmp = TempFile::new("missing_final_newline", b"a\nb\r\nc");
+        let file = tmp.map();
+        assert_eq!(file.len(), 3);
+        assert_eq!(all_lines(&file), vec!["a", "b", "c"]);
+
+        let tmp = TempFile::new("only_newline", b"\n");
+        assert_eq!(all_lines(&tmp.map()), vec![""]);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let tmp = TempFile::new(
+            "invalid_utf8",
+            b"valid \xc3\xa4\ninvalid \xc3\n\xff\xfe\r\n",
+        );
+        let file = tmp.map();
+        assert_eq!(
+            all_lines(&file),
+            vec!["valid \u{e4}", "invalid \u{fffd}", "\u{fffd}\u{fffd}"]
+        );
+        // Converted lines are only converted once.
+        assert!(::std::ptr::eq(file.line(1).unwrap(), file.line(1).unwrap()));
+    }
+
+    #[test]
+    fn converted_lines_across_chunks() {
+        let num_lines = CONVERTED_CHUNK_SIZE + 2;
+        // Lines `CONVERTED_CHUNK_SIZE - 1` and `CONVERTED_CHUNK_SIZE` both contain invalid UTF-8.
+        let is_invalid = |i: usize| i % 3 != 2;
+        let mut data = Vec::new();
+        for i in 0..num_lines {
+            data.extend_from_slice(i.to_string().as_bytes());
+            if is_invalid(i) {
+                data.push(0xff);
+            }
+            data.push(b'\n');
+        }
+        let tmp = TempFile::new("converted_lines_across_chunks", &data);
+        let file = tmp.map();
+
+        // Access lines around the chunk boundary first, so that chunks are not filled in order.
+        let last_in_first_chunk = file.line(CONVERTED_CHUNK_SIZE - 1).unwrap();
+        let first_in_second_chunk = file.line(CONVERTED_CHUNK_SIZE).unwrap();
+        let first = file.line(0).unwrap();
+        assert_eq!(first, "0\u{fffd}");
+        assert_eq!(file.len(), num_lines);
+        for (i, line) in all_lines(&file).into_iter().enumerate() {
+            if is_invalid(i) {
+                assert_eq!(line, format!("{}\u{fffd}", i));
+            } else {
+                assert_eq!(line, i.to_string());
+            }
+        }
+        assert_eq!(
+            last_in_first_chunk,
+            format!("{}\u{fffd}", CONVERTED_CHUNK_SIZE - 1)
+        );
+        assert_eq!(
+            first_in_second_chunk,
+            format!("{}\u{fffd}", CONVERTED_CHUNK_SIZE)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+/// A `less`-like status line that displays information about the state of a `Pager`.
+use unsegen::base::{Cursor, StyleModifier, Window, WrappingMode};
+use unsegen::widget::{Demand, Demand2D, RenderingHints, Widget};
+
+use super::{LineDecorator, Pager, PagerLine};
+
+use std::fmt::Write;
+
+/// The format used by `StatusLine` unless another one is specified using `with_format`.
+pub const DEFAULT_STATUS_FORMAT: &str = "%f  %l/%L  %p  %F";
+
+/// A widget that displays information about a `Pager` in a single row, e.g., below the pager.
+///
+/// What is displayed is specified by a format string (see `with_format`), in which the following
+/// placeholders are replaced:
+///
+/// * `%f`: The name of the content (see `PagerContent::with_name`)
+/// * `%l`: The number of the active line (starting at 1)
+/// * `%L`: The total number of lines, or `?` if it is not known without locating all lines
+/// * `%p`: How far the last visible line is through the content (in percent), or `?` if the total
+///   number of lines is not known
+/// * `%s`: The current search pattern
+/// * `%m`: The number of matches of the current search pattern (requires scanning the whole
+///   content)
+/// * `%F`: `following` if the pager currently follows new lines (see `Pager::set_follow_mode`)
+/// * `%%`: A literal `%`
+///
+/// All other characters are displayed as they are.
+pub struct StatusLine<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    pager: &'a Pager<L, D>,
+    format: String,
+    style: StyleModifier,
+}
+
+impl<'a, L, D> StatusLine<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    /// Create a status line for the given pager using `DEFAULT_STATUS_FORMAT` and inverted text.
+    pub fn new(pager: &'a Pager<L, D>) -> Self {
+        StatusLine {
+            pager,
+            format: DEFAULT_STATUS_FORMAT.to_owned(),
+            style: StyleModifier::new().invert(true),
+        }
+    }
+
+    /// Specify what is displayed using a format string (see `StatusLine`).
+    pub fn with_format<S: Into<String>>(mut self, format: S) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Set the style of the whole status line.
+    pub fn with_style(mut self, style: StyleModifier) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Get the text of the status line, i.e., the format string with all placeholders replaced.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        let mut chars = self.format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('f') => {
+                    if let Some(name) = self.pager.content().and_then(|c| c.name()) {
+                        text.push_str(name);
+                    }
+                }
+                Some('l') => {
+                    let line = self.pager.current_line_index().raw_value() + 1;
+                    let _ = write!(text, "{}", line);
+                }
+                Some('L') => match self.num_lines() {
+                    Some(num_lines) => {
+                        let _ = write!(text, "{}", num_lines);
+                    }
+                    None => text.push('?'),
+                },
+                Some('p') => match self.percentage() {
+                    Some(percentage) => {
+                        let _ = write!(text, "{}%", percentage);
+                    }
+                    None => text.push('?'),
+                },
+                Some('s') => {
+                    if let Some(pattern) = self.pager.search_pattern() {
+                        text.push_str(pattern);
+                    }
+                }
+                Some('m') => {
+                    let _ = write!(text, "{}", self.pager.search_match_count());
+                }
+                Some('F') => {
+                    if self.pager.is_following() {
+                        text.push_str("following");
+                    }
+                }
+                Some('%') => text.push('%'),
+                Some(other) => {
+                    text.push('%');
+                    text.push(other);
+                }
+                None => text.push('%'),
+            }
+        }
+        text
+    }
+
+    fn num_lines(&self) -> Option<usize> {
+        self.pager
+            .content()
+            .and_then(|content| content.num_lines_if_known())
+    }
+
+    fn percentage(&self) -> Option<usize> {
+        let num_lines = self.num_lines().filter(|&n| n > 0)?;
+        let last_visible_line = self
+            .pager
+            .layout()
+            .last_visible_line()
+            .unwrap_or_else(|| self.pager.current_line_index());
+        Some(::std::cmp::min(last_visible_line.raw_value() + 1, num_lines) * 100 / num_lines)
+    }
+}
+
+impl<'a, L, D> Widget for StatusLine<'a, L, D>
+where
+    L: PagerLine + ?Sized + 'a,
+    D: LineDecorator<Line = L> + 'a,
+{
+    fn space_demand(&self) -> Demand2D {
+        Demand2D {
+            width: Demand::at_least(1),
+            height: Demand::exact(1),
+        }
+    }
+    fn draw(&self, mut window: Window, _: RenderingHints) {
+        window.modify_default_style(self.style);
+        window.clear();
+        let mut cursor = Cursor::new(&mut window).wrapping_mode(WrappingMode::NoWrap);
+        cursor.write(&self.text());
+    }
+}
@@ -0,0 +1,330 @@
+/// Helpers for working with the display columns of (lines of) text.
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use unsegen::base::StyleModifier;
+
+use std::ops::Range;
+
+/// Number of columns between two tab stops, which is the default of `unsegen`'s `Cursor`.
+///
+/// Tabs are expanded to the next tab stop relative to the start of the line (see `expand_tabs`).
+pub const TAB_WIDTH: usize = 4;
+
+/// Number of columns that `grapheme` occupies if it is displayed at (line) column `column`.
+fn grapheme_width(grapheme: &str, column: usize) -> usize {
+    if grapheme == "\t" {
+        TAB_WIDTH - column % TAB_WIDTH
+    } else {
+        grapheme.width()
+    }
+}
+
+/// All grapheme clusters of `text` together with their byte position, the display column they
+/// start at and their width.
+fn graphemes_with_columns<'a>(
+    text: &'a str,
+) -> impl Iterator<Item = (usize, &'a str, usize, usize)> + 'a {
+    let mut column = 0;
+    text.grapheme_indices(true).map(move |(pos, grapheme)| {
+        let start = column;
+        let width = grapheme_width(grapheme, start);
+        column += width;
+        (pos, grapheme, start, width)
+    })
+}
+
+/// Number of columns required to display the given text.
+pub fn width(text: &str) -> usize {
+    graphemes_with_columns(text)
+        .last()
+        .map_or(0, |(_, _, column, width)| column + width)
+}
+
+/// Find the byte position in `text` of the first grapheme cluster that starts at or after display
+/// column `column`.
+///
+/// The second element of the result is the number of columns between `column` and the start of
+/// that grapheme cluster, which is non-zero if `column` lies within a wide grapheme cluster (or a
+/// tab).
+pub fn byte_pos_at_column(text: &str, column: usize) -> (usize, usize) {
+    let mut end_column = 0;
+    for (pos, _, start, width) in graphemes_with_columns(text) {
+        if start >= column {
+            return (pos, start - column);
+        }
+        end_column = start + width;
+    }
+    (text.len(), end_column.saturating_sub(column))
+}
+
+/// Get the longest prefix of `text` that can be displayed in `max_width` columns.
+pub fn truncate_to_width(text: &str, max_width: usize) -> &str {
+    for (pos, _, start, width) in graphemes_with_columns(text) {
+        if start + width > max_width {
+            return &text[..pos];
+        }
+    }
+    text
+}
+
+/// Find the grapheme cluster that is displayed at display column `column`, or the last grapheme
+/// cluster if the text is narrower.
+///
+/// Returns the byte range of the grapheme cluster (which is empty for empty texts) and the display
+/// column at which it starts.
+pub fn grapheme_at_column(text: &str, column: usize) -> (Range<usize>, usize) {
+    let mut last = (0..0, 0);
+    for (pos, grapheme, start, width) in graphemes_with_columns(text) {
+        last = (pos..pos + grapheme.len(), start);
+        if column < start + width {
+            break;
+        }
+    }
+    last
+}
+
+/// The cells that `text` occupies if it is wrapped to rows of `row_width` columns, as tuples of
+/// the row, the column within the row, the column within the whole line and the width.
+///
+/// As when drawing the text, grapheme clusters that do not fit into the rest of a row start on
+/// the next row. Tabs are drawn as spaces (see `expand_tabs`) and may thus be split across rows.
+fn wrapped_cells<'a>(
+    text: &'a str,
+    row_width: usize,
+) -> impl Iterator<Item = (usize, usize, usize, usize)> + 'a {
+    graphemes_with_columns(text)
+        .flat_map(|(_, grapheme, column, width)| {
+            let (num_cells, cell_width) = if grapheme == "\t" {
+                (width, 1)
+            } else {
+                (1, width)
+            };
+            (0..num_cells).map(move |i| (column + i, cell_width))
+        })
+        .scan((0, 0), move |state, (column, width)| {
+            let (ref mut row, ref mut row_column) = *state;
+            if *row_column > 0 && *row_column + width > row_width {
+                *row += 1;
+                *row_column = 0;
+            }
+            let cell = (*row, *row_column, column, width);
+            *row_column += width;
+            Some(cell)
+        })
+}
+
+/// Number of rows required to display `text` if it is wrapped to rows of `row_width` columns.
+///
+/// Empty texts occupy a single row.
+pub fn num_wrapped_rows(text: &str, row_width: usize) -> usize {
+    wrapped_cells(text, row_width)
+        .last()
+        .map_or(1, |(row, _, _, _)| row + 1)
+}
+
+/// Find the display column (within the whole line) that is displayed at `column` in row `row` when
+/// `text` is wrapped to rows of `row_width` columns.
+///
+/// Positions after the end of a row map to its last grapheme cluster.
+pub fn column_at_wrapped_position(
+    text: &str,
+    row_width: usize,
+    row: usize,
+    column: usize,
+) -> usize {
+    let mut last_column_in_row = None;
+    let mut end_column = 0;
+    for (cell_row, row_column, line_column, width) in wrapped_cells(text, row_width) {
+        if cell_row > row {
+            if let Some(last_column) = last_column_in_row {
+                return last_column;
+            }
+        }
+        if cell_row == row {
+            if column < row_column + width {
+                return line_column;
+            }
+            last_column_in_row = Some(line_column);
+        }
+        end_column = line_column + width;
+    }
+    end_column
+}
+
+/// Get the byte position of the grapheme cluster before the one starting at byte position `pos`.
+pub fn previous_grapheme_pos(text: &str, pos: usize) -> Option<usize> {
+    text[..pos]
+        .grapheme_indices(true)
+        .next_back()
+        .map(|(p, _)| p)
+}
+
+fn is_word(segment: &str) -> bool {
+    segment
+        .chars()
+        .next()
+        .map(|c| c.is_alphanumeric() || c == '_')
+        .unwrap_or(false)
+}
+
+/// Byte ranges of all words (according to the Unicode word boundaries) in the text.
+pub fn words<'a>(text: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+    text.split_word_bound_indices()
+        .filter(|&(_, segment)| is_word(segment))
+        .map(|(pos, segment)| pos..pos + segment.len())
+}
+
+/// Byte range of the maximal run of non-whitespace characters that contains byte position `pos`,
+/// if there is one.
+pub fn token_at(text: &str, pos: usize) -> Option<Range<usize>> {
+    match text[pos..].chars().next() {
+        Some(c) if !c.is_whitespace() => {}
+        _ => return None,
+    }
+    let start = text[..pos]
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| c.is_whitespace())
+        .map_or(0, |(p, c)| p + c.len_utf8());
+    let end = text[pos..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |p| pos + p);
+    Some(start..end)
+}
+
+/// Replace all tabs in a line by spaces up to the next tab stop and adjust its style changes
+/// accordingly.
+///
+/// Drawing the expanded line makes the tab stops independent of where (and whether) the line is
+/// cut off or wrapped.
+pub fn expand_tabs(
+    text: &str,
+    style_changes: &[(usize, StyleModifier)],
+) -> (String, Vec<(usize, StyleModifier)>) {
+    let mut expanded = String::with_capacity(text.len());
+    // Byte positions after tabs together with the number of bytes inserted up to there.
+    let mut shifts = Vec::new();
+    for (pos, grapheme, _, width) in graphemes_with_columns(text) {
+        if grapheme == "\t" {
+            expanded.push_str(&" ".repeat(width));
+            shifts.push((pos + 1, expanded.len() - (pos + 1)));
+        } else {
+            expanded.push_str(grapheme);
+        }
+    }
+    let expanded_changes = style_changes
+        .iter()
+        .map(|&(pos, style)| {
+            let shift = shifts
+                .iter()
+                .take_while(|&&(tab_end, _)| tab_end <= pos)
+                .last()
+                .map_or(0, |&(_, shift)| shift);
+            (pos + shift, style)
+        })
+        .collect();
+    (expanded, expanded_changes)
+}
+
+/// Cut off the first `column` display columns of a line (with tabs expanded, see `expand_tabs`)
+/// and adjust its style changes accordingly.
+///
+/// Partially cut off grapheme clusters are replaced by spaces.
+pub fn cut_off_columns(
+    text: &str,
+    style_changes: &[(usize, StyleModifier)],
+    column: usize,
+) -> (String, Vec<(usize, StyleModifier)>) {
+    let (text, style_changes) = expand_tabs(text, style_changes);
+    let (text, style_changes) = (&text[..], &style_changes[..]);
+    let (skip, padding) = byte_pos_at_column(text, column);
+    let mut cut_text = " ".repeat(padding);
+    cut_text.push_str(&text[skip..]);
+
+    let initial_style = style_changes
+        .iter()
+        .take_while(|&&(pos, _)| pos <= skip)
+        .last()
+        .map(|&(_, style)| (0, style));
+    let cut_changes = initial_style
+        .into_iter()
+        .chain(
+            style_changes
+                .iter()
+                .filter(|&&(pos, _)| pos > skip)
+                .map(|&(pos, style)| (pos - skip + padding, style)),
+        )
+        .collect();
+    (cut_text, cut_changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabs_expand_to_tab_stops() {
+        assert_eq!(width("\t"), 4);
+        assert_eq!(width("ab\tc"), 5);
+        assert_eq!(width("abcd\t"), 8);
+        assert_eq!(width("a\u{e4}\t\t"), 8);
+
+        assert_eq!(byte_pos_at_column("ab\tc", 2), (2, 0));
+        assert_eq!(byte_pos_at_column("ab\tc", 3), (3, 1));
+        assert_eq!(truncate_to_width("ab\tc", 3), "ab");
+        assert_eq!(truncate_to_width("ab\tc", 4), "ab\t");
+        assert_eq!(grapheme_at_column("ab\tc", 3), (2..3, 2));
+        assert_eq!(grapheme_at_column("ab\tc", 4), (3..4, 4));
+    }
+
+    #[test]
+    fn expand_and_cut_off_tabs() {
+        let style = StyleModifier::new().bold(true);
+        assert_eq!(
+            expand_tabs("a\tb\t", &[(0, style), (1, style), (2, style), (4, style)]),
+            (
+                "a   b   ".to_owned(),
+                vec![(0, style), (1, style), (4, style), (8, style)]
+            )
+        );
+        assert_eq!(
+            cut_off_columns("a\tb", &[(0, style), (2, style)], 2),
+            ("  b".to_owned(), vec![(0, style), (2, style)])
+        );
+        // Tab stops do not depend on the number of cut off columns.
+        assert_eq!(cut_off_columns("\tb\tc", &[], 5).0, "   c");
+    }
+
+    #[test]
+    fn wrapped_rows() {
+        assert_eq!(num_wrapped_rows("", 4), 1);
+        assert_eq!(num_wrapped_rows("abcd", 4), 1);
+        assert_eq!(num_wrapped_rows("abcde", 4), 2);
+        // Wide grapheme clusters that do not fit start on the next row.
+        assert_eq!(num_wrapped_rows("abc\u{ff21}", 4), 2);
+        assert_eq!(num_wrapped_rows("ab\u{ff21}", 4), 1);
+        // Tabs are split across rows.
+        assert_eq!(num_wrapped_rows("ab\t", 3), 2);
+        assert_eq!(num_wrapped_rows("\t\t", 8), 1);
+        assert_eq!(num_wrapped_rows("a\tb", 5), 1);
+        assert_eq!(num_wrapped_rows("a\tbc", 5), 2);
+    }
+
+    #[test]
+    fn wrapped_positions() {
+        let text = "ab\tc\u{ff21}d";
+        // Rows of 5 columns: "ab  c", "\u{ff21}d"
+        assert_eq!(column_at_wrapped_position(text, 5, 0, 0), 0);
+        assert_eq!(column_at_wrapped_position(text, 5, 0, 3), 3);
+        assert_eq!(column_at_wrapped_position(text, 5, 0, 4), 4);
+        assert_eq!(column_at_wrapped_position(text, 5, 1, 1), 5);
+        assert_eq!(column_at_wrapped_position(text, 5, 1, 2), 7);
+        assert_eq!(column_at_wrapped_position(text, 5, 1, 4), 8);
+        assert_eq!(column_at_wrapped_position(text, 5, 2, 0), 8);
+        // A tab split across rows.
+        assert_eq!(column_at_wrapped_position("abc\t", 3, 1, 0), 3);
+        assert_eq!(column_at_wrapped_position("abc\tde", 3, 0, 5), 2);
+        assert_eq!(grapheme_at_column("abc\t", 3), (3..4, 3));
+    }
+}